@@ -0,0 +1,109 @@
+//! Waiting on a condition variable of the render thread, without blocking forever if the render
+//! thread stops without notifying it.
+
+use std::sync::{Condvar, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+/// Interval in which waiting checks whether the render thread is still alive.
+const ALIVE_CHECK_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Outcome of `wait_while_alive`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum AliveWait {
+    /// The condition doesn't hold anymore.
+    Done,
+    TimedOut,
+    /// The render thread stopped while the condition still held.
+    Closed,
+}
+
+/// Blocks while `condition` holds for the guarded value, but at most for `timeout`. Waiting
+/// stops early as soon as `is_alive` returns `false`, which is checked on every notification and
+/// at least every `ALIVE_CHECK_INTERVAL`.
+pub(crate) fn wait_while_alive<T, C: FnMut(&mut T) -> bool, A: Fn() -> bool>(
+    condvar: &Condvar,
+    mut guard: MutexGuard<'_, T>,
+    timeout: Option<Duration>,
+    mut condition: C,
+    is_alive: A,
+) -> AliveWait {
+    let started = Instant::now();
+    while condition(&mut guard) {
+        if !is_alive() {
+            return AliveWait::Closed;
+        }
+        let mut wait = ALIVE_CHECK_INTERVAL;
+        if let Some(timeout) = timeout {
+            let elapsed = started.elapsed();
+            if elapsed >= timeout {
+                return AliveWait::TimedOut;
+            }
+            wait = wait.min(timeout - elapsed);
+        }
+        guard = condvar
+            .wait_timeout(guard, wait)
+            .unwrap_or_else(PoisonError::into_inner)
+            .0;
+    }
+    AliveWait::Done
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+
+    #[test]
+    fn returns_once_the_condition_is_notified_away() {
+        let state = Arc::new((Mutex::new(false), Condvar::new()));
+        let notifier = Arc::clone(&state);
+        let handle = thread::spawn(move || {
+            *notifier.0.lock().unwrap() = true;
+            notifier.1.notify_all();
+        });
+        let outcome = wait_while_alive(
+            &state.1,
+            state.0.lock().unwrap(),
+            None,
+            |done| !*done,
+            || true,
+        );
+        handle.join().unwrap();
+        assert_eq!(outcome, AliveWait::Done);
+    }
+
+    #[test]
+    fn times_out_while_the_condition_holds() {
+        let state = (Mutex::new(()), Condvar::new());
+        let started = Instant::now();
+        let outcome = wait_while_alive(
+            &state.1,
+            state.0.lock().unwrap(),
+            Some(Duration::from_millis(10)),
+            |_| true,
+            || true,
+        );
+        assert_eq!(outcome, AliveWait::TimedOut);
+        assert!(started.elapsed() < ALIVE_CHECK_INTERVAL * 10);
+    }
+
+    #[test]
+    fn stops_without_notification_once_not_alive() {
+        let state = (Mutex::new(()), Condvar::new());
+        let checks = Mutex::new(0);
+        let outcome = wait_while_alive(
+            &state.1,
+            state.0.lock().unwrap(),
+            None,
+            |_| true,
+            || {
+                let mut checks = checks.lock().unwrap();
+                *checks += 1;
+                *checks < 3
+            },
+        );
+        assert_eq!(outcome, AliveWait::Closed);
+    }
+}
@@ -0,0 +1,132 @@
+//! Interactive camera, which zooms and pans the drawn view on top of the preferred view of the
+//! environment.
+
+use piston_window::{Button, ButtonArgs, ButtonState, Input, Key, Motion, MouseButton};
+
+use gymnarium_base::math::{Position2D, Transformation2D};
+
+/// Factor the camera zooms by per scrolled step of the mouse wheel.
+const ZOOM_PER_SCROLL_STEP: f64 = 1.1f64;
+const MIN_ZOOM: f64 = 0.05f64;
+const MAX_ZOOM: f64 = 100f64;
+
+/* --- --- --- Camera --- --- --- */
+
+/// Zoom and pan applied after the preferred view, so `Camera::default()` shows the preferred
+/// view as it is.
+///
+/// Positions are given in normalized window coordinates, where the window spans from `-1` to
+/// `1` on both axes with `y` pointing upwards.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Camera {
+    /// Factor the view is magnified by.
+    pub zoom: f64,
+    /// Translation of the view, applied after zooming around the center of the window.
+    pub offset: (f64, f64),
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self {
+            zoom: 1f64,
+            offset: (0f64, 0f64),
+        }
+    }
+}
+
+impl Camera {
+    /// Zooms by `factor` while keeping the given position at the same place in the window.
+    pub fn zoom_around(&mut self, factor: f64, position: (f64, f64)) {
+        let zoom = (self.zoom * factor).clamp(MIN_ZOOM, MAX_ZOOM);
+        let applied_factor = zoom / self.zoom;
+        self.offset = (
+            position.0 - (position.0 - self.offset.0) * applied_factor,
+            position.1 - (position.1 - self.offset.1) * applied_factor,
+        );
+        self.zoom = zoom;
+    }
+
+    pub fn pan(&mut self, delta: (f64, f64)) {
+        self.offset = (self.offset.0 + delta.0, self.offset.1 + delta.1);
+    }
+
+    /// Maps a position in normalized window coordinates as drawn by this camera back to the
+    /// position it has without the camera.
+    pub fn unproject(&self, position: (f64, f64)) -> (f64, f64) {
+        (
+            (position.0 - self.offset.0) / self.zoom,
+            (position.1 - self.offset.1) / self.zoom,
+        )
+    }
+
+    pub(crate) fn transformation(&self) -> Transformation2D {
+        Transformation2D::composition(
+            "Camera".to_string(),
+            vec![
+                Transformation2D::scale(self.zoom, self.zoom),
+                Transformation2D::translation(
+                    Position2D::zero().vector_to(&Position2D::with(self.offset.0, self.offset.1)),
+                ),
+            ],
+        )
+    }
+}
+
+/// Converts a position in pixels of a window with the given size into normalized window
+/// coordinates.
+pub(crate) fn normalized_window_position(position: [f64; 2], window_size: [f64; 2]) -> (f64, f64) {
+    (
+        position[0] / window_size[0] * 2f64 - 1f64,
+        1f64 - position[1] / window_size[1] * 2f64,
+    )
+}
+
+/* --- --- --- CameraController --- --- --- */
+
+/// Moves the camera by the inputs of the window: the mouse wheel zooms around the cursor,
+/// dragging with the right mouse button pans and the home key resets the camera.
+#[derive(Default)]
+pub(crate) struct CameraController {
+    pub camera: Camera,
+    cursor: Option<[f64; 2]>,
+    panning: bool,
+}
+
+impl CameraController {
+    pub fn handle_input(&mut self, input: &Input, window_size: [f64; 2]) {
+        match input {
+            Input::Move(Motion::MouseCursor(cursor)) => {
+                if let (true, Some(last_cursor)) = (self.panning, self.cursor) {
+                    self.camera.pan((
+                        (cursor[0] - last_cursor[0]) / window_size[0] * 2f64,
+                        (last_cursor[1] - cursor[1]) / window_size[1] * 2f64,
+                    ));
+                }
+                self.cursor = Some(*cursor);
+            }
+            Input::Move(Motion::MouseScroll([_, scroll])) => {
+                let position = self
+                    .cursor
+                    .map(|cursor| normalized_window_position(cursor, window_size))
+                    .unwrap_or((0f64, 0f64));
+                self.camera
+                    .zoom_around(ZOOM_PER_SCROLL_STEP.powf(*scroll), position);
+            }
+            Input::Button(ButtonArgs {
+                state,
+                button: Button::Mouse(MouseButton::Right),
+                ..
+            }) => self.panning = *state == ButtonState::Press,
+            Input::Button(ButtonArgs {
+                state: ButtonState::Press,
+                button: Button::Keyboard(Key::Home),
+                ..
+            }) => self.camera = Camera::default(),
+            Input::Cursor(false) => {
+                self.cursor = None;
+                self.panning = false;
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,220 @@
+//! Capturing of the frames presented by the `PistonVisualiser` into numbered PNG files.
+//!
+//! `FrameSinks` bundles everything that consumes presented frames, so the back buffer is read
+//! at most once per frame.
+
+use std::error::Error;
+use std::fmt::Display;
+use std::path::PathBuf;
+use std::sync::mpsc::Sender;
+
+use gfx_device_gl::Device;
+
+use image::RgbaImage;
+
+use crate::recording::EpisodeRecorder;
+use crate::PistonVisualiserError;
+
+/* --- --- --- FrameCaptureSettings --- --- --- */
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrameCaptureSettings {
+    directory: PathBuf,
+    file_prefix: String,
+    every_nth_frame: u64,
+}
+
+impl FrameCaptureSettings {
+    /// Captures every presented frame as `frame_000000.png`, `frame_000001.png`, ... into
+    /// `directory`, which is created if it doesn't exist.
+    pub fn new<P: Into<PathBuf>>(directory: P) -> Self {
+        Self {
+            directory: directory.into(),
+            file_prefix: "frame_".to_string(),
+            every_nth_frame: 1,
+        }
+    }
+
+    pub fn file_prefix<S: Into<String>>(mut self, file_prefix: S) -> Self {
+        self.file_prefix = file_prefix.into();
+        self
+    }
+
+    /// Captures only every `n`th presented frame.
+    pub fn every_nth_frame(mut self, n: u64) -> Self {
+        self.every_nth_frame = n.max(1);
+        self
+    }
+
+    pub fn directory(&self) -> &PathBuf {
+        &self.directory
+    }
+}
+
+/* --- --- --- CapturedFrame --- --- --- */
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapturedFrame {
+    /// Number of the frame counted from the start of the capture, also used in the file name.
+    pub frame_number: u64,
+    pub path: PathBuf,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrameCaptureError {
+    pub path: PathBuf,
+    pub cause: String,
+}
+
+impl Display for FrameCaptureError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Could not write captured frame to {} (cause: {})",
+            self.path.display(),
+            self.cause
+        )
+    }
+}
+
+impl Error for FrameCaptureError {}
+
+/* --- --- --- FrameCapture --- --- --- */
+
+pub(crate) struct FrameCapture {
+    settings: FrameCaptureSettings,
+    presented_frames: u64,
+    captured_frames: u64,
+    sender: Sender<Result<CapturedFrame, FrameCaptureError>>,
+}
+
+impl FrameCapture {
+    pub fn new(
+        settings: FrameCaptureSettings,
+        sender: Sender<Result<CapturedFrame, FrameCaptureError>>,
+    ) -> Self {
+        Self {
+            settings,
+            presented_frames: 0,
+            captured_frames: 0,
+            sender,
+        }
+    }
+
+    /// Counts the presented frame and returns whether it has to be captured.
+    pub fn next_frame_wanted(&mut self) -> bool {
+        let wanted = self.presented_frames % self.settings.every_nth_frame == 0;
+        self.presented_frames += 1;
+        wanted
+    }
+
+    pub fn write(&mut self, frame: &RgbaImage) {
+        let frame_number = self.captured_frames;
+        self.captured_frames += 1;
+        let path = self.settings.directory.join(format!(
+            "{}{:06}.png",
+            self.settings.file_prefix, frame_number
+        ));
+        let result = match frame.save(&path) {
+            Ok(()) => Ok(CapturedFrame { frame_number, path }),
+            Err(error) => Err(FrameCaptureError {
+                path,
+                cause: format!("{}", error),
+            }),
+        };
+        // The receiving side may have been dropped, if nobody is interested in the paths.
+        let _ = self.sender.send(result);
+    }
+}
+
+/* --- --- --- FrameSinks --- --- --- */
+
+#[derive(Default)]
+pub(crate) struct FrameSinks {
+    pub frame_capture: Option<FrameCapture>,
+    pub episode_recorder: Option<EpisodeRecorder>,
+}
+
+impl FrameSinks {
+    /// Hands the presented frame to every sink which wants it. `read_back` is only called if at
+    /// least one of them does, its error is returned and the frame skipped by all sinks.
+    pub fn present<F: FnOnce() -> Result<RgbaImage, PistonVisualiserError>>(
+        &mut self,
+        read_back: F,
+    ) -> Result<(), PistonVisualiserError> {
+        let capture_wanted = self
+            .frame_capture
+            .as_mut()
+            .map(|frame_capture| frame_capture.next_frame_wanted())
+            .unwrap_or(false);
+        let recording_repetitions = self
+            .episode_recorder
+            .as_mut()
+            .map(|episode_recorder| episode_recorder.frames_due())
+            .unwrap_or(0);
+        if !capture_wanted && recording_repetitions == 0 {
+            return Ok(());
+        }
+
+        let frame = read_back()?;
+        if capture_wanted {
+            if let Some(frame_capture) = self.frame_capture.as_mut() {
+                frame_capture.write(&frame);
+            }
+        }
+        if recording_repetitions > 0 {
+            if let Some(episode_recorder) = self.episode_recorder.as_mut() {
+                episode_recorder.write(frame, recording_repetitions);
+            }
+        }
+        if self
+            .episode_recorder
+            .as_ref()
+            .map(|episode_recorder| episode_recorder.is_finished())
+            .unwrap_or(false)
+        {
+            self.episode_recorder = None;
+        }
+        Ok(())
+    }
+}
+
+/// Reads the current content of the back buffer, which has to be done after drawing and before
+/// the buffers are swapped.
+pub(crate) fn read_back_frame(
+    device: &mut Device,
+    width: u32,
+    height: u32,
+) -> Result<RgbaImage, PistonVisualiserError> {
+    let mut pixels = vec![0u8; width as usize * height as usize * 4];
+    // SAFETY: The device belongs to the window of the calling thread, whose GL context is
+    // current, since piston makes it current when the window is created and it's never used by
+    // another thread. With a pack alignment of 1 the rows are tightly packed, so reading
+    // `width` x `height` RGBA pixels of one byte per channel writes exactly the
+    // `width * height * 4` bytes of `pixels`, which outlives the call.
+    unsafe {
+        device.with_gl(|gl| {
+            gl.PixelStorei(gfx_gl::PACK_ALIGNMENT, 1);
+            gl.ReadPixels(
+                0,
+                0,
+                width as i32,
+                height as i32,
+                gfx_gl::RGBA,
+                gfx_gl::UNSIGNED_BYTE,
+                pixels.as_mut_ptr() as *mut _,
+            );
+        });
+    }
+    // The alpha channel of the window is meaningless, so the frame is made opaque.
+    pixels.chunks_exact_mut(4).for_each(|pixel| pixel[3] = 255);
+    let mut frame = RgbaImage::from_raw(width, height, pixels).ok_or_else(|| {
+        PistonVisualiserError::FrameReadBackFailed(format!(
+            "the read back pixels don't match the frame size of {}x{}",
+            width, height
+        ))
+    })?;
+    // OpenGL starts with the bottom row.
+    image::imageops::flip_vertical_in_place(&mut frame);
+    Ok(frame)
+}
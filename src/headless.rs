@@ -0,0 +1,1136 @@
+//! Headless visualiser, which rasterises all geometries on the CPU into an `image::RgbaImage`
+//! instead of drawing them into a window.
+
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+
+use image::{Rgba, RgbaImage};
+
+use rusttype::{point, Font, Scale};
+
+use gymnarium_base::math::{matrix_3x3_as_matrix_3x2, Position2D};
+use gymnarium_visualisers_base::{
+    CornerShape, Geometry2D, LineShape, TextureSource, TwoDimensionalDrawableEnvironment,
+    TwoDimensionalVisualiser, Visualiser,
+};
+
+use crate::triangulation::PolygonFillsIter;
+use crate::{
+    Diagnostics, ExtendedTwoDimensionalDrawableEnvironment, FontSource,
+    FurtherPistonVisualiserError, PistonVisualiser, PistonVisualiserError,
+    PistonVisualiserSyncedData, Text2D,
+};
+
+const ELLIPSE_RESOLUTION: usize = 64;
+const ROUND_CAP_RESOLUTION: usize = 16;
+
+/* --- --- --- HeadlessTextureBuffer --- --- --- */
+
+#[derive(Default)]
+struct HeadlessTextureBuffer {
+    buffered_textures: HashMap<TextureSource, RgbaImage>,
+    used_textures: HashSet<TextureSource>,
+}
+
+impl HeadlessTextureBuffer {
+    /// Loads the texture if it isn't buffered yet, falling back to a placeholder like the
+    /// `PistonVisualiser` does.
+    pub fn load_or_mark_use(&mut self, texture_source: &TextureSource, diagnostics: &Diagnostics) {
+        if !self.buffered_textures.contains_key(texture_source) {
+            let loaded = crate::load_texture_image(texture_source).unwrap_or_else(|error| {
+                diagnostics.push(PistonVisualiserError::TextureLoadFailed(error));
+                crate::placeholder_texture_image(texture_source)
+            });
+            let _ = self
+                .buffered_textures
+                .insert(texture_source.clone(), loaded);
+        }
+        let _ = self.used_textures.insert(texture_source.clone());
+    }
+
+    pub fn drop_unused(&mut self) {
+        let used_textures = std::mem::take(&mut self.used_textures);
+        self.buffered_textures
+            .retain(|texture_source, _| used_textures.contains(texture_source));
+    }
+
+    pub fn get(&self, texture_source: &TextureSource) -> Option<&RgbaImage> {
+        self.buffered_textures.get(texture_source)
+    }
+
+    fn mark_geometries(&mut self, geometry_2ds: &[Geometry2D], diagnostics: &Diagnostics) {
+        geometry_2ds.iter().for_each(|geometry| match geometry {
+            Geometry2D::Image { texture_source, .. } => {
+                self.load_or_mark_use(texture_source, diagnostics)
+            }
+            Geometry2D::Group(geometries) => self.mark_geometries(geometries, diagnostics),
+            _ => {}
+        });
+    }
+}
+
+/* --- --- --- HeadlessFontBuffer --- --- --- */
+
+#[derive(Default)]
+struct HeadlessFontBuffer {
+    /// Fonts which couldn't be loaded are kept as `None`, like in the `PistonVisualiser`.
+    buffered_fonts: HashMap<FontSource, Option<Font<'static>>>,
+}
+
+impl HeadlessFontBuffer {
+    pub fn load(&mut self, font_source: &FontSource, diagnostics: &Diagnostics) {
+        if !self.buffered_fonts.contains_key(font_source) {
+            let loaded = match font_source {
+                FontSource::Path(path) => std::fs::read(path)
+                    .map_err(|error| format!("Could not load {} as font (cause: {})", path, error))
+                    .and_then(|bytes| {
+                        Font::try_from_vec(bytes)
+                            .ok_or_else(|| format!("Could not load {} as font", path))
+                    }),
+                _ => Font::try_from_bytes(font_source.bytes().unwrap())
+                    .ok_or_else(|| "Could not load font from bytes".to_string()),
+            };
+            let loaded = loaded
+                .map_err(|cause| diagnostics.push(PistonVisualiserError::FontLoadFailed(cause)))
+                .ok();
+            let _ = self.buffered_fonts.insert(font_source.clone(), loaded);
+        }
+    }
+
+    pub fn get(&self, font_source: &FontSource) -> Option<&Font<'static>> {
+        self.buffered_fonts
+            .get(font_source)
+            .and_then(Option::as_ref)
+    }
+}
+
+/* --- --- --- Canvas --- --- --- */
+
+/// Rasterises primitives given in the local coordinates of a geometry onto an `RgbaImage`.
+///
+/// Local coordinates are transformed with the (3x2) transformation matrix of the geometry into
+/// normalized device coordinates, exactly like the matrix is used as transform for piston, and
+/// then mapped onto the pixels of the image.
+struct Canvas<'a> {
+    image: &'a mut RgbaImage,
+    scissor: Option<[u32; 4]>,
+}
+
+impl<'a> Canvas<'a> {
+    fn width(&self) -> f64 {
+        self.image.width() as f64
+    }
+
+    fn height(&self) -> f64 {
+        self.image.height() as f64
+    }
+
+    fn clear(&mut self, color: [f32; 4]) {
+        let pixel = Rgba(Self::to_u8(color));
+        self.image.pixels_mut().for_each(|p| *p = pixel);
+    }
+
+    fn pixel_transform(&self, transform: [[f64; 3]; 2]) -> [[f64; 3]; 2] {
+        let (hw, hh) = (self.width() / 2f64, self.height() / 2f64);
+        [
+            [
+                hw * transform[0][0],
+                hw * transform[0][1],
+                hw * transform[0][2] + hw,
+            ],
+            [
+                -hh * transform[1][0],
+                -hh * transform[1][1],
+                -hh * transform[1][2] + hh,
+            ],
+        ]
+    }
+
+    fn apply(transform: &[[f64; 3]; 2], point: [f64; 2]) -> [f64; 2] {
+        [
+            transform[0][0] * point[0] + transform[0][1] * point[1] + transform[0][2],
+            transform[1][0] * point[0] + transform[1][1] * point[1] + transform[1][2],
+        ]
+    }
+
+    fn to_u8(color: [f32; 4]) -> [u8; 4] {
+        let c = |v: f32| (v.clamp(0f32, 1f32) * 255f32).round() as u8;
+        [c(color[0]), c(color[1]), c(color[2]), c(color[3])]
+    }
+
+    fn blend(&mut self, x: i64, y: i64, color: [f32; 4], use_scissor: bool) {
+        if x < 0 || y < 0 || x >= self.image.width() as i64 || y >= self.image.height() as i64 {
+            return;
+        }
+        if use_scissor {
+            if let Some([sx, sy, sw, sh]) = self.scissor {
+                let (sx, sy, sw, sh) = (sx as i64, sy as i64, sw as i64, sh as i64);
+                if x < sx || y < sy || x >= sx + sw || y >= sy + sh {
+                    return;
+                }
+            }
+        }
+        let pixel = self.image.get_pixel_mut(x as u32, y as u32);
+        let alpha = color[3].clamp(0f32, 1f32);
+        let destination_alpha = pixel.0[3] as f32 / 255f32;
+        let out_alpha = alpha + destination_alpha * (1f32 - alpha);
+        if out_alpha <= 0f32 {
+            pixel.0 = [0, 0, 0, 0];
+            return;
+        }
+        let mut out = [0f32; 4];
+        for (channel, value) in out.iter_mut().enumerate().take(3) {
+            let destination = pixel.0[channel] as f32 / 255f32;
+            *value = (color[channel] * alpha + destination * destination_alpha * (1f32 - alpha))
+                / out_alpha;
+        }
+        out[3] = out_alpha;
+        pixel.0 = Self::to_u8(out);
+    }
+
+    /// Fills the area enclosed by the given contours (in local coordinates) with the even-odd
+    /// rule, sampling at the pixel centers.
+    fn fill_contours(
+        &mut self,
+        contours: &[Vec<[f64; 2]>],
+        color: [f32; 4],
+        transform: [[f64; 3]; 2],
+    ) {
+        if color[3] <= 0f32 {
+            return;
+        }
+        let pixel_transform = self.pixel_transform(transform);
+        let edges = contours
+            .iter()
+            .filter(|contour| contour.len() >= 3)
+            .flat_map(|contour| {
+                let transformed = contour
+                    .iter()
+                    .map(|point| Self::apply(&pixel_transform, *point))
+                    .collect::<Vec<[f64; 2]>>();
+                (0..transformed.len())
+                    .map(|index| {
+                        (
+                            transformed[index],
+                            transformed[(index + 1) % transformed.len()],
+                        )
+                    })
+                    .collect::<Vec<([f64; 2], [f64; 2])>>()
+            })
+            .filter(|(from, to)| from[1] != to[1])
+            .collect::<Vec<([f64; 2], [f64; 2])>>();
+        if edges.is_empty() {
+            return;
+        }
+
+        let min_y = edges
+            .iter()
+            .map(|(from, to)| from[1].min(to[1]))
+            .fold(f64::INFINITY, f64::min)
+            .floor()
+            .max(0f64) as i64;
+        let max_y = edges
+            .iter()
+            .map(|(from, to)| from[1].max(to[1]))
+            .fold(f64::NEG_INFINITY, f64::max)
+            .ceil()
+            .min(self.height()) as i64;
+
+        let mut crossings = Vec::new();
+        for y in min_y..max_y {
+            let sample_y = y as f64 + 0.5f64;
+            crossings.clear();
+            for (from, to) in &edges {
+                let (top, bottom) = if from[1] < to[1] {
+                    (from, to)
+                } else {
+                    (to, from)
+                };
+                if top[1] <= sample_y && sample_y < bottom[1] {
+                    let t = (sample_y - top[1]) / (bottom[1] - top[1]);
+                    crossings.push(top[0] + t * (bottom[0] - top[0]));
+                }
+            }
+            crossings.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+            for span in crossings.chunks_exact(2) {
+                let start = (span[0] - 0.5f64).ceil().max(0f64) as i64;
+                let end = (span[1] - 0.5f64).ceil().min(self.width()) as i64;
+                for x in start..end {
+                    self.blend(x, y, color, true);
+                }
+            }
+        }
+    }
+
+    fn fill_polygon(&mut self, points: &[[f64; 2]], color: [f32; 4], transform: [[f64; 3]; 2]) {
+        self.fill_contours(&[points.to_vec()], color, transform);
+    }
+
+    fn circle_points(center: [f64; 2], radius: f64, resolution: usize) -> Vec<[f64; 2]> {
+        Self::ellipse_points(
+            [
+                center[0] - radius,
+                center[1] - radius,
+                2f64 * radius,
+                2f64 * radius,
+            ],
+            resolution,
+        )
+    }
+
+    fn ellipse_points(rectangle: [f64; 4], resolution: usize) -> Vec<[f64; 2]> {
+        let (cx, cy) = (
+            rectangle[0] + rectangle[2] / 2f64,
+            rectangle[1] + rectangle[3] / 2f64,
+        );
+        let (rx, ry) = (rectangle[2] / 2f64, rectangle[3] / 2f64);
+        (0..resolution)
+            .map(|index| {
+                let angle = index as f64 / resolution as f64 * std::f64::consts::PI * 2f64;
+                [cx + rx * angle.cos(), cy + ry * angle.sin()]
+            })
+            .collect()
+    }
+
+    fn rectangle_points(rectangle: [f64; 4], corner_shape: &CornerShape) -> Vec<[f64; 2]> {
+        let [x, y, w, h] = rectangle;
+        match corner_shape {
+            CornerShape::Square => vec![[x, y], [x + w, y], [x + w, y + h], [x, y + h]],
+            CornerShape::Bevel(size) => {
+                let s = size.max(0f64).min(w.abs() / 2f64).min(h.abs() / 2f64);
+                vec![
+                    [x + s, y],
+                    [x + w - s, y],
+                    [x + w, y + s],
+                    [x + w, y + h - s],
+                    [x + w - s, y + h],
+                    [x + s, y + h],
+                    [x, y + h - s],
+                    [x, y + s],
+                ]
+            }
+            CornerShape::Round(size, resolution) => {
+                let s = size.max(0f64).min(w.abs() / 2f64).min(h.abs() / 2f64);
+                let resolution = (*resolution).max(1) as usize;
+                let corners = [
+                    ([x + w - s, y + s], -0.5f64),
+                    ([x + w - s, y + h - s], 0f64),
+                    ([x + s, y + h - s], 0.5f64),
+                    ([x + s, y + s], 1f64),
+                ];
+                corners
+                    .iter()
+                    .flat_map(|(center, start)| {
+                        (0..=resolution).map(move |index| {
+                            let angle = (start + index as f64 / resolution as f64 * 0.5f64)
+                                * std::f64::consts::PI;
+                            [center[0] + s * angle.cos(), center[1] + s * angle.sin()]
+                        })
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    fn draw_line(
+        &mut self,
+        from: [f64; 2],
+        to: [f64; 2],
+        radius: f64,
+        shape: &LineShape,
+        color: [f32; 4],
+        transform: [[f64; 3]; 2],
+    ) {
+        let (dx, dy) = (to[0] - from[0], to[1] - from[1]);
+        let length = (dx * dx + dy * dy).sqrt();
+        if length > 0f64 {
+            let (nx, ny) = (-dy / length * radius, dx / length * radius);
+            self.fill_polygon(
+                &[
+                    [from[0] + nx, from[1] + ny],
+                    [to[0] + nx, to[1] + ny],
+                    [to[0] - nx, to[1] - ny],
+                    [from[0] - nx, from[1] - ny],
+                ],
+                color,
+                transform,
+            );
+        }
+        match shape {
+            LineShape::Square => {}
+            LineShape::Round => {
+                for end in [from, to].iter() {
+                    self.fill_polygon(
+                        &Self::circle_points(*end, radius, ROUND_CAP_RESOLUTION),
+                        color,
+                        transform,
+                    );
+                }
+            }
+            LineShape::Bevel => {
+                if length > 0f64 {
+                    let (ux, uy) = (dx / length * radius, dy / length * radius);
+                    let (nx, ny) = (-uy, ux);
+                    self.fill_polygon(
+                        &[
+                            [to[0] + nx, to[1] + ny],
+                            [to[0] + ux, to[1] + uy],
+                            [to[0] - nx, to[1] - ny],
+                        ],
+                        color,
+                        transform,
+                    );
+                    self.fill_polygon(
+                        &[
+                            [from[0] - nx, from[1] - ny],
+                            [from[0] - ux, from[1] - uy],
+                            [from[0] + nx, from[1] + ny],
+                        ],
+                        color,
+                        transform,
+                    );
+                }
+            }
+        }
+    }
+
+    fn draw_polygon_border(
+        &mut self,
+        points: &[[f64; 2]],
+        border_color: [f32; 4],
+        border_width: f64,
+        transform: [[f64; 3]; 2],
+    ) {
+        for index in 0..points.len() {
+            self.draw_line(
+                points[index],
+                points[(index + 1) % points.len()],
+                border_width,
+                &LineShape::Round,
+                border_color,
+                transform,
+            );
+        }
+    }
+
+    fn draw_rectangle(
+        &mut self,
+        rectangle: [f64; 4],
+        corner_shape: &CornerShape,
+        fill_color: [f32; 4],
+        border_color: [f32; 4],
+        border_radius: f64,
+        transform: [[f64; 3]; 2],
+    ) {
+        self.fill_polygon(
+            &Self::rectangle_points(rectangle, corner_shape),
+            fill_color,
+            transform,
+        );
+        if border_radius > 0f64 {
+            let [x, y, w, h] = rectangle;
+            let grown_corner_shape = match corner_shape {
+                CornerShape::Square => CornerShape::Square,
+                CornerShape::Round(size, resolution) => {
+                    CornerShape::Round(size + border_radius, *resolution)
+                }
+                CornerShape::Bevel(size) => CornerShape::Bevel(size + border_radius),
+            };
+            let shrunk_corner_shape = match corner_shape {
+                CornerShape::Square => CornerShape::Square,
+                CornerShape::Round(size, resolution) => {
+                    CornerShape::Round((size - border_radius).max(0f64), *resolution)
+                }
+                CornerShape::Bevel(size) => CornerShape::Bevel((size - border_radius).max(0f64)),
+            };
+            let mut contours = vec![Self::rectangle_points(
+                [
+                    x - border_radius,
+                    y - border_radius,
+                    w + 2f64 * border_radius,
+                    h + 2f64 * border_radius,
+                ],
+                &grown_corner_shape,
+            )];
+            if w > 2f64 * border_radius && h > 2f64 * border_radius {
+                contours.push(Self::rectangle_points(
+                    [
+                        x + border_radius,
+                        y + border_radius,
+                        w - 2f64 * border_radius,
+                        h - 2f64 * border_radius,
+                    ],
+                    &shrunk_corner_shape,
+                ));
+            }
+            self.fill_contours(&contours, border_color, transform);
+        }
+    }
+
+    fn draw_ellipse(
+        &mut self,
+        rectangle: [f64; 4],
+        fill_color: [f32; 4],
+        border_color: [f32; 4],
+        border_radius: f64,
+        transform: [[f64; 3]; 2],
+    ) {
+        self.fill_polygon(
+            &Self::ellipse_points(rectangle, ELLIPSE_RESOLUTION),
+            fill_color,
+            transform,
+        );
+        if border_radius > 0f64 {
+            let [x, y, w, h] = rectangle;
+            let mut contours = vec![Self::ellipse_points(
+                [
+                    x - border_radius,
+                    y - border_radius,
+                    w + 2f64 * border_radius,
+                    h + 2f64 * border_radius,
+                ],
+                ELLIPSE_RESOLUTION,
+            )];
+            if w.abs() > 2f64 * border_radius && h.abs() > 2f64 * border_radius {
+                contours.push(Self::ellipse_points(
+                    [
+                        x + border_radius,
+                        y + border_radius,
+                        w - 2f64 * border_radius,
+                        h - 2f64 * border_radius,
+                    ],
+                    ELLIPSE_RESOLUTION,
+                ));
+            }
+            self.fill_contours(&contours, border_color, transform);
+        }
+    }
+
+    fn draw_text(&mut self, font: &Font<'static>, text: &Text2D) {
+        let scale = Scale::uniform(text.font_size as f32);
+        let text_width = font
+            .layout(&text.text, scale, point(0f32, 0f32))
+            .last()
+            .map(|glyph| glyph.position().x + glyph.unpositioned().h_metrics().advance_width)
+            .unwrap_or(0f32);
+        let offset = text.anchor.offset(text_width as f64, text.font_size);
+        let position = text.pixel_position([self.width(), self.height()]);
+        let origin = point(
+            (position[0] + offset[0]) as f32,
+            (position[1] + offset[1]) as f32,
+        );
+        let color = text.color.float_array();
+        for glyph in font.layout(&text.text, scale, origin) {
+            if let Some(bounding_box) = glyph.pixel_bounding_box() {
+                glyph.draw(|x, y, coverage| {
+                    self.blend(
+                        bounding_box.min.x as i64 + x as i64,
+                        bounding_box.min.y as i64 + y as i64,
+                        [color[0], color[1], color[2], color[3] * coverage],
+                        true,
+                    )
+                });
+            }
+        }
+    }
+
+    fn draw_image(
+        &mut self,
+        texture: &RgbaImage,
+        rectangle: [f64; 4],
+        source_rectangle: Option<[f64; 4]>,
+        color: [f32; 4],
+        transform: [[f64; 3]; 2],
+    ) {
+        let pixel_transform = self.pixel_transform(transform);
+        let [a, b, c] = pixel_transform[0];
+        let [d, e, f] = pixel_transform[1];
+        let determinant = a * e - b * d;
+        if determinant == 0f64 || rectangle[2] == 0f64 || rectangle[3] == 0f64 {
+            return;
+        }
+        let inverse = [
+            [
+                e / determinant,
+                -b / determinant,
+                (b * f - c * e) / determinant,
+            ],
+            [
+                -d / determinant,
+                a / determinant,
+                (c * d - a * f) / determinant,
+            ],
+        ];
+
+        let [x, y, w, h] = rectangle;
+        let corners = [[x, y], [x + w, y], [x + w, y + h], [x, y + h]]
+            .iter()
+            .map(|corner| Self::apply(&pixel_transform, *corner))
+            .collect::<Vec<[f64; 2]>>();
+        let min_x = corners.iter().map(|p| p[0]).fold(f64::INFINITY, f64::min);
+        let max_x = corners
+            .iter()
+            .map(|p| p[0])
+            .fold(f64::NEG_INFINITY, f64::max);
+        let min_y = corners.iter().map(|p| p[1]).fold(f64::INFINITY, f64::min);
+        let max_y = corners
+            .iter()
+            .map(|p| p[1])
+            .fold(f64::NEG_INFINITY, f64::max);
+
+        let (texture_width, texture_height) = (texture.width() as f64, texture.height() as f64);
+        let [sx, sy, sw, sh] =
+            source_rectangle.unwrap_or([0f64, 0f64, texture_width, texture_height]);
+
+        for py in (min_y.floor().max(0f64) as i64)..(max_y.ceil().min(self.height()) as i64) {
+            for px in (min_x.floor().max(0f64) as i64)..(max_x.ceil().min(self.width()) as i64) {
+                let local = Self::apply(&inverse, [px as f64 + 0.5f64, py as f64 + 0.5f64]);
+                let (u, v) = ((local[0] - x) / w, (local[1] - y) / h);
+                if !(0f64..1f64).contains(&u) || !(0f64..1f64).contains(&v) {
+                    continue;
+                }
+                let tx = (sx + u * sw).floor();
+                let ty = (sy + v * sh).floor();
+                if tx < 0f64 || ty < 0f64 || tx >= texture_width || ty >= texture_height {
+                    continue;
+                }
+                let texel = texture.get_pixel(tx as u32, ty as u32).0;
+                self.blend(
+                    px,
+                    py,
+                    [
+                        texel[0] as f32 / 255f32 * color[0],
+                        texel[1] as f32 / 255f32 * color[1],
+                        texel[2] as f32 / 255f32 * color[2],
+                        texel[3] as f32 / 255f32 * color[3],
+                    ],
+                    true,
+                );
+            }
+        }
+    }
+}
+
+/* --- --- --- HeadlessVisualiser --- --- --- */
+
+/// Visualiser which doesn't need a display or a GPU.
+///
+/// Every call to `render_two_dimensional` rasterises all geometries on the CPU into an
+/// `image::RgbaImage` of the configured size, handling the preferred view exactly like the
+/// `PistonVisualiser` does, so that an environment looks the same with both visualisers.
+pub struct HeadlessVisualiser {
+    frame: RgbaImage,
+    closed: bool,
+    texture_buffer: HeadlessTextureBuffer,
+    font_buffer: HeadlessFontBuffer,
+    diagnostics: Diagnostics,
+}
+
+impl HeadlessVisualiser {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            frame: RgbaImage::new(width, height),
+            closed: false,
+            texture_buffer: HeadlessTextureBuffer::default(),
+            font_buffer: HeadlessFontBuffer::default(),
+            diagnostics: Diagnostics::default(),
+        }
+    }
+
+    /// Returns the most recently rendered frame.
+    pub fn frame(&self) -> &RgbaImage {
+        &self.frame
+    }
+
+    /// Returns and forgets the errors recovered from since the last call, like
+    /// `PistonVisualiser::take_diagnostics`.
+    pub fn take_diagnostics(&self) -> Vec<PistonVisualiserError> {
+        self.diagnostics.take()
+    }
+
+    /// Renders the geometries and additionally the texts of the environment.
+    pub fn render_extended_two_dimensional<
+        DrawableEnvironmentError: Error,
+        DrawableEnvironment: ExtendedTwoDimensionalDrawableEnvironment<DrawableEnvironmentError>,
+    >(
+        &mut self,
+        drawable_environment: &DrawableEnvironment,
+    ) -> Result<(), FurtherPistonVisualiserError<DrawableEnvironmentError>> {
+        let data = PistonVisualiser::extended_synced_data_of(drawable_environment)?;
+        self.render_frame(&data);
+        Ok(())
+    }
+
+    pub(crate) fn render_frame(&mut self, data: &PistonVisualiserSyncedData) {
+        self.texture_buffer
+            .mark_geometries(&data.geometry_2ds, &self.diagnostics);
+        data.texts
+            .iter()
+            .for_each(|text| self.font_buffer.load(&text.font, &self.diagnostics));
+
+        let polygon_fills = crate::triangulate_polygons(&data.geometry_2ds, &self.diagnostics);
+
+        let viewport_rect = [0, 0, self.frame.width() as i32, self.frame.height() as i32];
+        let (scissor, transform) =
+            PistonVisualiser::view_transformation(&data.preferred_view, viewport_rect);
+
+        let mut canvas = Canvas {
+            image: &mut self.frame,
+            scissor,
+        };
+        canvas.clear(
+            data.background_color
+                .map(|c| c.float_array())
+                .unwrap_or([0f32; 4]),
+        );
+        let mut polygon_fills = polygon_fills.iter();
+        for geometry_2d in data.geometry_2ds.iter() {
+            Self::render_geometry_2d(
+                &mut canvas,
+                &geometry_2d.clone().append_transformation(transform.clone()),
+                &self.texture_buffer,
+                &mut polygon_fills,
+            );
+        }
+        for text in data.texts.iter() {
+            if let Some(font) = self.font_buffer.get(&text.font) {
+                canvas.draw_text(font, &text.clone().append_transformation(transform.clone()));
+            }
+        }
+
+        self.texture_buffer.drop_unused();
+    }
+
+    fn render_geometry_2d(
+        canvas: &mut Canvas,
+        geometry_2d: &Geometry2D,
+        texture_buffer: &HeadlessTextureBuffer,
+        polygon_fills: &mut PolygonFillsIter,
+    ) {
+        match geometry_2d {
+            Geometry2D::Point {
+                position,
+                color,
+                transformations,
+            } => {
+                let transformed_position: Position2D = position.transform(transformations);
+                canvas.blend(
+                    ((transformed_position.x + 1f64) / 2f64 * canvas.width()).floor() as i64,
+                    ((transformed_position.y + 1f64) / 2f64 * canvas.height()).floor() as i64,
+                    color.float_array(),
+                    false,
+                );
+            }
+            Geometry2D::Line {
+                points,
+                line_color,
+                line_width,
+                line_shape,
+                transformations,
+            } => canvas.draw_line(
+                [points[0].x, points[0].y],
+                [points[1].x, points[1].y],
+                *line_width,
+                line_shape,
+                line_color.float_array(),
+                matrix_3x3_as_matrix_3x2(transformations.transformation_matrix()),
+            ),
+            Geometry2D::Polyline {
+                points,
+                line_color,
+                line_width,
+                line_shape,
+                transformations,
+            } => {
+                for segment in points.windows(2) {
+                    canvas.draw_line(
+                        [segment[0].x, segment[0].y],
+                        [segment[1].x, segment[1].y],
+                        *line_width,
+                        line_shape,
+                        line_color.float_array(),
+                        matrix_3x3_as_matrix_3x2(transformations.transformation_matrix()),
+                    );
+                }
+            }
+            Geometry2D::Triangle {
+                points,
+                fill_color,
+                border_color,
+                border_width,
+                transformations,
+            } => {
+                let polygon = [
+                    [points[0].x, points[0].y],
+                    [points[1].x, points[1].y],
+                    [points[2].x, points[2].y],
+                ];
+                let transform = matrix_3x3_as_matrix_3x2(transformations.transformation_matrix());
+                canvas.fill_polygon(&polygon, fill_color.float_array(), transform);
+                canvas.draw_polygon_border(
+                    &polygon,
+                    border_color.float_array(),
+                    *border_width,
+                    transform,
+                );
+            }
+            Geometry2D::Square {
+                center_position,
+                edge_length,
+                fill_color,
+                border_color,
+                border_width,
+                corner_shape,
+                transformations,
+            } => canvas.draw_rectangle(
+                [
+                    center_position.x - edge_length / 2f64,
+                    center_position.y - edge_length / 2f64,
+                    *edge_length,
+                    *edge_length,
+                ],
+                corner_shape,
+                fill_color.float_array(),
+                border_color.float_array(),
+                *border_width,
+                matrix_3x3_as_matrix_3x2(transformations.transformation_matrix()),
+            ),
+            Geometry2D::Rectangle {
+                center_position,
+                size,
+                fill_color,
+                border_color,
+                border_width,
+                corner_shape,
+                transformations,
+            } => canvas.draw_rectangle(
+                [
+                    center_position.x - size.width / 2f64,
+                    center_position.y - size.height / 2f64,
+                    size.width,
+                    size.height,
+                ],
+                corner_shape,
+                fill_color.float_array(),
+                border_color.float_array(),
+                *border_width,
+                matrix_3x3_as_matrix_3x2(transformations.transformation_matrix()),
+            ),
+            Geometry2D::Polygon {
+                points,
+                fill_color,
+                border_color,
+                border_width,
+                transformations,
+            } => {
+                let polygon: Vec<[f64; 2]> = points
+                    .iter()
+                    .map(|position| [position.x, position.y])
+                    .collect();
+                let transform = matrix_3x3_as_matrix_3x2(transformations.transformation_matrix());
+                // Filled with the same triangles as in the `PistonVisualiser`, so polygons which
+                // couldn't be triangulated stay unfilled there as well as here.
+                for triangle in polygon_fills.next().into_iter().flatten() {
+                    canvas.fill_polygon(triangle, fill_color.float_array(), transform);
+                }
+                canvas.draw_polygon_border(
+                    &polygon,
+                    border_color.float_array(),
+                    *border_width,
+                    transform,
+                );
+            }
+            Geometry2D::Circle {
+                center_position,
+                radius,
+                fill_color,
+                border_color,
+                border_width,
+                transformations,
+            } => canvas.draw_ellipse(
+                [
+                    center_position.x - radius,
+                    center_position.y - radius,
+                    2f64 * radius,
+                    2f64 * radius,
+                ],
+                fill_color.float_array(),
+                border_color.float_array(),
+                *border_width,
+                matrix_3x3_as_matrix_3x2(transformations.transformation_matrix()),
+            ),
+            Geometry2D::Ellipse {
+                center_position,
+                size,
+                fill_color,
+                border_color,
+                border_width,
+                transformations,
+            } => canvas.draw_ellipse(
+                [
+                    center_position.x - size.width,
+                    center_position.y - size.height,
+                    size.width,
+                    size.height,
+                ],
+                fill_color.float_array(),
+                border_color.float_array(),
+                *border_width,
+                matrix_3x3_as_matrix_3x2(transformations.transformation_matrix()),
+            ),
+            Geometry2D::Image {
+                center_position,
+                size,
+                texture_source,
+                source_rectangle,
+                fill_color,
+                transformations,
+            } => {
+                if let Some(texture) = texture_buffer.get(texture_source) {
+                    canvas.draw_image(
+                        texture,
+                        [
+                            center_position.x - size.width / 2f64,
+                            center_position.y - size.height / 2f64,
+                            size.width,
+                            size.height,
+                        ],
+                        source_rectangle.map(|(src_pos, src_siz)| {
+                            [
+                                src_pos.x - src_siz.width / 2f64,
+                                src_pos.y - src_siz.height / 2f64,
+                                src_siz.width,
+                                src_siz.height,
+                            ]
+                        }),
+                        fill_color.map(|fc| fc.float_array()).unwrap_or([1f32; 4]),
+                        matrix_3x3_as_matrix_3x2(transformations.transformation_matrix()),
+                    );
+                }
+            }
+            Geometry2D::Group(geometries) => {
+                for geometry in geometries {
+                    Self::render_geometry_2d(canvas, geometry, texture_buffer, polygon_fills);
+                }
+            }
+        }
+    }
+}
+
+impl Visualiser<PistonVisualiserError> for HeadlessVisualiser {
+    fn is_open(&self) -> bool {
+        !self.closed
+    }
+
+    fn close(&mut self) -> Result<(), PistonVisualiserError> {
+        self.closed = true;
+        Ok(())
+    }
+}
+
+impl<DrawableEnvironmentError: Error + 'static>
+    TwoDimensionalVisualiser<
+        FurtherPistonVisualiserError<DrawableEnvironmentError>,
+        PistonVisualiserError,
+        DrawableEnvironmentError,
+    > for HeadlessVisualiser
+{
+    fn render_two_dimensional<
+        DrawableEnvironment: TwoDimensionalDrawableEnvironment<DrawableEnvironmentError>,
+    >(
+        &mut self,
+        drawable_environment: &DrawableEnvironment,
+    ) -> Result<(), FurtherPistonVisualiserError<DrawableEnvironmentError>> {
+        let data = PistonVisualiser::synced_data_of(drawable_environment)?;
+        self.render_frame(&data);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use gymnarium_base::math::{Size2D, Transformation2D};
+    use gymnarium_visualisers_base::Color;
+
+    use crate::triangulation::PolygonFills;
+
+    fn rectangle(center: [f64; 2], size: [f64; 2]) -> Geometry2D {
+        Geometry2D::Rectangle {
+            center_position: Position2D::with(center[0], center[1]),
+            size: Size2D::with(size[0], size[1]),
+            fill_color: Color::black(),
+            border_color: Color::black(),
+            border_width: 0f64,
+            corner_shape: CornerShape::Square,
+            transformations: Transformation2D::identity(),
+        }
+    }
+
+    fn line(from: [f64; 2], to: [f64; 2], line_width: f64) -> Geometry2D {
+        Geometry2D::Line {
+            points: [
+                Position2D::with(from[0], from[1]),
+                Position2D::with(to[0], to[1]),
+            ],
+            line_color: Color::black(),
+            line_width,
+            line_shape: LineShape::Square,
+            transformations: Transformation2D::identity(),
+        }
+    }
+
+    fn polygon(points: &[[f64; 2]], border_width: f64) -> Geometry2D {
+        Geometry2D::Polygon {
+            points: points
+                .iter()
+                .map(|point| Position2D::with(point[0], point[1]))
+                .collect(),
+            fill_color: Color::black(),
+            border_color: Color::black(),
+            border_width,
+            transformations: Transformation2D::identity(),
+        }
+    }
+
+    fn render(visualiser: &mut HeadlessVisualiser, geometry_2ds: Vec<Geometry2D>) {
+        visualiser.render_frame(&PistonVisualiserSyncedData {
+            geometry_2ds: geometry_2ds.into(),
+            ..Default::default()
+        });
+    }
+
+    fn covered_pixels(image: &RgbaImage) -> Vec<(u32, u32)> {
+        image
+            .enumerate_pixels()
+            .filter(|(_, _, pixel)| pixel.0[3] > 0)
+            .map(|(x, y, _)| (x, y))
+            .collect()
+    }
+
+    #[test]
+    fn fills_exactly_the_pixels_of_a_rectangle() {
+        let mut visualiser = HeadlessVisualiser::new(8, 8);
+        render(&mut visualiser, vec![rectangle([0f64, 0f64], [1f64, 1f64])]);
+        let expected = (2..6)
+            .flat_map(|y| (2..6).map(move |x| (x, y)))
+            .collect::<Vec<(u32, u32)>>();
+        assert_eq!(covered_pixels(visualiser.frame()), expected);
+        assert_eq!(visualiser.frame().get_pixel(3, 3).0, [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn clips_a_line_at_the_image_bounds() {
+        let mut visualiser = HeadlessVisualiser::new(8, 8);
+        render(
+            &mut visualiser,
+            vec![line([-3f64, 0f64], [3f64, 0f64], 0.25f64)],
+        );
+        let expected = (3..5)
+            .flat_map(|y| (0..8).map(move |x| (x, y)))
+            .collect::<Vec<(u32, u32)>>();
+        assert_eq!(covered_pixels(visualiser.frame()), expected);
+    }
+
+    #[test]
+    fn clips_a_line_at_the_scissor() {
+        let mut image = RgbaImage::new(8, 8);
+        let mut canvas = Canvas {
+            image: &mut image,
+            scissor: Some([0, 0, 4, 8]),
+        };
+        HeadlessVisualiser::render_geometry_2d(
+            &mut canvas,
+            &line([-1f64, 0f64], [1f64, 0f64], 0.25f64),
+            &HeadlessTextureBuffer::default(),
+            &mut PolygonFills::default().iter(),
+        );
+        let expected = (3..5)
+            .flat_map(|y| (0..4).map(move |x| (x, y)))
+            .collect::<Vec<(u32, u32)>>();
+        assert_eq!(covered_pixels(&image), expected);
+    }
+
+    #[test]
+    fn clears_the_previous_frame_with_the_background_color() {
+        let mut visualiser = HeadlessVisualiser::new(4, 4);
+        render(&mut visualiser, vec![rectangle([0f64, 0f64], [2f64, 2f64])]);
+        assert_eq!(covered_pixels(visualiser.frame()).len(), 16);
+
+        render(&mut visualiser, Vec::new());
+        assert!(covered_pixels(visualiser.frame()).is_empty());
+
+        visualiser.render_frame(&PistonVisualiserSyncedData {
+            background_color: Some(Color::black()),
+            ..Default::default()
+        });
+        assert!(visualiser
+            .frame()
+            .pixels()
+            .all(|pixel| pixel.0 == [0, 0, 0, 255]));
+    }
+
+    #[test]
+    fn fills_a_concave_polygon_with_its_triangles() {
+        let points = [
+            [-0.75f64, -0.75f64],
+            [0.75f64, -0.75f64],
+            [0.75f64, 0.75f64],
+            [0f64, 0f64],
+            [-0.75f64, 0.75f64],
+        ];
+        let mut visualiser = HeadlessVisualiser::new(16, 16);
+        render(&mut visualiser, vec![polygon(&points, 0f64)]);
+        assert!(visualiser.take_diagnostics().is_empty());
+
+        let (polygon_fills, errors) = PolygonFills::of(&[polygon(&points, 0f64)]);
+        assert!(errors.is_empty());
+        let mut expected = RgbaImage::new(16, 16);
+        let mut canvas = Canvas {
+            image: &mut expected,
+            scissor: None,
+        };
+        let identity = [[1f64, 0f64, 0f64], [0f64, 1f64, 0f64]];
+        for triangle in polygon_fills.iter().flatten() {
+            canvas.fill_polygon(triangle, [0f32, 0f32, 0f32, 1f32], identity);
+        }
+        assert_eq!(visualiser.frame(), &expected);
+        // The notch between the two upper corners stays empty.
+        assert_eq!(visualiser.frame().get_pixel(8, 3).0[3], 0);
+        assert_eq!(visualiser.frame().get_pixel(8, 12).0[3], 255);
+    }
+
+    #[test]
+    fn draws_only_the_border_of_a_self_intersecting_polygon() {
+        let points = [
+            [-0.75f64, -0.75f64],
+            [0.75f64, 0.75f64],
+            [0.75f64, -0.75f64],
+            [-0.75f64, 0.75f64],
+        ];
+        let mut visualiser = HeadlessVisualiser::new(16, 16);
+        render(&mut visualiser, vec![polygon(&points, 0.05f64)]);
+        let diagnostics = visualiser.take_diagnostics();
+        assert_eq!(diagnostics.len(), 1);
+        assert!(matches!(
+            diagnostics[0],
+            PistonVisualiserError::PolygonTriangulationFailed(_)
+        ));
+
+        let mut expected = RgbaImage::new(16, 16);
+        let mut canvas = Canvas {
+            image: &mut expected,
+            scissor: None,
+        };
+        canvas.draw_polygon_border(
+            &points,
+            [0f32, 0f32, 0f32, 1f32],
+            0.05f64,
+            [[1f64, 0f64, 0f64], [0f64, 1f64, 0f64]],
+        );
+        assert_eq!(visualiser.frame(), &expected);
+        // Inside of the left wing, which an even-odd fill would cover.
+        assert_eq!(visualiser.frame().get_pixel(3, 8).0[3], 0);
+    }
+}
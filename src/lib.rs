@@ -3,33 +3,147 @@
 //! `gymnarium_visualisers_piston` contains visualisers and further structures for the
 //! `gymnarium_libraries` utilizing the Piston crates.
 //!
-//! ## Problems
+//! ## Visualisers
 //!
-//! ### Non Convex Polygons
+//! - `PistonVisualiser` opens a window and draws the environment in a separate render thread.
+//!   The window can be configured with `PistonVisualiserSettings` and `run_with_settings`.
+//! - `PumpedPistonVisualiser` opens the window on the calling thread instead, which then has to
+//!   call `pump_events` and `present` itself.
+//! - `HeadlessVisualiser` rasterises the environment on the CPU into an image, for machines
+//!   without a display or a GPU.
+//! - `ObservationRenderer` and `render_to_observation` render the environment into flat pixel
+//!   buffers, which can be used as observations for agents.
 //!
-//! This crate is not able to visualise non convex polygons, because I couldn't find something
-//! in the piston framework nor in crates.io and I didn't want to implement it myself.
+//! ## Camera
+//!
+//! The windows can have an interactive `Camera` on top of the preferred view of the environment,
+//! which is enabled with `PistonVisualiserSettings::interactive_camera`: the mouse wheel zooms
+//! around the cursor, dragging with the right mouse button pans and the home key resets it.
+//!
+//! ## Inputs
+//!
+//! The inputs of the windows end up in a `PistonVisualiserInputProvider`. Mouse and touch
+//! inputs can additionally be popped with their `EnvironmentPosition`, which is the position in
+//! the coordinates of the environment, taking the preferred view and the camera into account.
+//! With `PistonVisualiserSettings::pick_events` the windows additionally report which geometry
+//! is hovered or clicked, `pick_geometry` does the same hit-testing for any geometries.
+//!
+//! ## Playback
+//!
+//! The user can pause the simulation with space, single-step it with `.` and request another
+//! speed with `+` and `-` in the window of a `PistonVisualiser`, unless disabled with
+//! `PistonVisualiserSettings::playback_hotkeys`. This only throttles the simulation if it calls
+//! `wait_while_paused` between its steps and respects `requested_speed`.
+//!
+//! ## Capturing
+//!
+//! The frames presented by a `PistonVisualiser` can be written as numbered PNG files with
+//! `start_frame_capture` or recorded into an animated GIF or an uncompressed Y4M video with
+//! `start_episode_recording`.
+//!
+//! ## Retained scene
+//!
+//! Besides the geometries rendered every step, a `PistonVisualiser` keeps a retained scene of
+//! geometries which are inserted once with `insert_geometry` and then only updated or removed
+//! through their `GeometryHandle`. Only these changes are handed over to the render thread,
+//! which suits static parts of an environment like walls.
+//!
+//! ## Unchanged frames
+//!
+//! A `PistonVisualiser` only hands a frame over to the render thread if it differs from the
+//! last one. `TwoDimensionalDrawableEnvironment` gives no hint whether anything changed, so
+//! `render_two_dimensional` still draws all geometries of the environment and compares them
+//! with the last frame, up to the first difference. Environments implementing
+//! `ExtendedTwoDimensionalDrawableEnvironment::frame_generation` skip both when rendered with
+//! `render_extended_two_dimensional`.
+//!
+//! ## Texts
+//!
+//! `Geometry2D` can't describe texts, so environments implementing
+//! `ExtendedTwoDimensionalDrawableEnvironment` can provide `Text2D`s in addition, which are
+//! rendered by `render_extended_two_dimensional`. Without a font given a font embedded into this
+//! crate (DejaVu Sans Mono) is used.
+//!
+//! ## Polygons
+//!
+//! Polygons are triangulated by ear clipping before they are drawn, so non convex polygons are
+//! visualised correctly. Every polygon is triangulated once per frame handed over to the window,
+//! not on every redraw. Self-intersecting polygons can't be triangulated, so only their border is
+//! drawn and a `PolygonTriangulationFailed` diagnostic is reported; `triangulate` can be used to
+//! check a polygon beforehand. The `HeadlessVisualiser` fills polygons with the same triangles. `Geometry2D::Polygon` can't describe holes, use
+//! `polygon_with_holes` to create a drawable group for them instead.
 
 extern crate gfx_device_gl;
 extern crate gymnarium_visualisers_base;
 extern crate image;
 extern crate piston_window;
 
-use std::collections::{HashMap, VecDeque};
+mod alive_wait;
+mod camera;
+mod capture;
+mod headless;
+mod observation;
+mod picking;
+mod playback;
+mod pointer;
+mod profiling;
+mod pumped;
+mod recording;
+mod scene;
+mod settings;
+mod statistics;
+mod text;
+mod triangulation;
+mod triple_buffer;
+
+pub use camera::Camera;
+pub use capture::{CapturedFrame, FrameCaptureError, FrameCaptureSettings};
+pub use headless::HeadlessVisualiser;
+pub use observation::{
+    render_to_observation, ColorFormat, ObservationComponent, ObservationRenderer,
+};
+pub use picking::{pick_geometry, PickEvent, PickedGeometry};
+pub use pointer::EnvironmentPosition;
+pub use profiling::{FrameProfile, PrimitiveCounts};
+pub use pumped::PumpedPistonVisualiser;
+pub use recording::{
+    EpisodeFormat, EpisodeRecorderSettings, EpisodeRecordingError, RecordedEpisode,
+};
+pub use scene::GeometryHandle;
+pub use settings::{PistonVisualiserSettings, RenderMode, TextureCachePolicy};
+pub use statistics::HandoffStatistics;
+pub use text::{FontSource, Text2D, TextAnchor};
+pub use triangulation::{polygon_with_holes, triangulate, TriangulationError};
+
+use std::any::Any;
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::error::Error;
 use std::fmt::Display;
+use std::panic::{self, AssertUnwindSafe};
 use std::sync::atomic::AtomicBool;
-use std::sync::{Arc, Mutex, Weak};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError, TryLockError, Weak};
 use std::thread;
 use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
 
 use gfx_device_gl::Device;
 
-use image::ImageBuffer;
+use alive_wait::{wait_while_alive, AliveWait};
+use camera::CameraController;
+use capture::{FrameCapture, FrameSinks};
+use playback::Playback;
+use recording::EpisodeRecorder;
+use scene::{RetainedScene, SceneChange};
+use triangulation::{PolygonFills, PolygonFillsIter};
+use triple_buffer::{triple_buffer, TripleBufferInput, TripleBufferOutput};
+
+use image::RgbaImage;
 
 use piston_window::{
-    Context, DrawState, Event, EventLoop, Flip, G2d, G2dTexture, Image, Loop, PistonWindow,
-    Texture, TextureSettings, Window, WindowSettings,
+    AdvancedWindow, AfterRenderArgs, CharacterCache, Context, DrawState, Event, EventLoop, G2d,
+    G2dTexture, Glyphs, Image, Loop, PistonWindow, RenderArgs, Texture, TextureSettings,
+    Transformed, Window, WindowSettings,
 };
 
 use gymnarium_base::math::{matrix_3x3_as_matrix_3x2, Position2D, Size2D, Transformation2D};
@@ -43,40 +157,177 @@ use gymnarium_visualisers_base::{
     TwoDimensionalVisualiser, Viewport2D, Viewport2DModification, Visualiser,
 };
 
+/// Time a dropped `PistonVisualiser` waits for its render thread.
+const DROP_CLOSE_TIMEOUT: Duration = Duration::from_secs(5);
+
 /* --- --- --- PistonVisualiserError --- --- --- */
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum PistonVisualiserError {
+    /// The render thread couldn't be joined while closing, contains the panic message.
     CloseCouldNotJoinRenderThread(String),
+    /// The render thread panicked, contains the panic message.
+    RenderThreadPanicked(String),
+    /// The directory for the captured frames couldn't be created.
+    CouldNotCreateCaptureDirectory(String),
+    /// The file for the episode recording couldn't be created.
+    CouldNotCreateRecordingFile(String),
+    /// The window couldn't be built, contains the cause reported by piston.
+    WindowCreationFailed(String),
+    /// A texture couldn't be loaded and is drawn as a placeholder instead.
+    TextureLoadFailed(String),
+    /// A texture or its placeholder couldn't be uploaded to the GPU, so it isn't drawn.
+    TextureUploadFailed(String),
+    /// A font couldn't be loaded, so the texts using it aren't drawn.
+    FontLoadFailed(String),
+    /// A thread panicked while using the input queue.
+    InputQueuePoisoned(String),
+    /// A thread panicked while holding a lock shared with the render thread.
+    LockingFailedInternally(String),
+    /// The render thread didn't stop within the given time.
+    CloseTimedOut(Duration),
+    /// The geometry was never inserted into the retained scene or was already removed.
+    UnknownGeometryHandle(GeometryHandle),
+    /// A polygon couldn't be triangulated, so it's drawn without fill.
+    PolygonTriangulationFailed(TriangulationError),
+    /// The presented frame couldn't be read back for the frame capture or episode recording.
+    FrameReadBackFailed(String),
 }
 
 impl Display for PistonVisualiserError {
-    fn fmt(&self, _f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        todo!()
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::CloseCouldNotJoinRenderThread(message) => {
+                write!(f, "Could not join the render thread ({})", message)
+            }
+            Self::RenderThreadPanicked(message) => {
+                write!(f, "The render thread panicked ({})", message)
+            }
+            Self::CouldNotCreateCaptureDirectory(cause) => {
+                write!(
+                    f,
+                    "Could not create the directory for captured frames: {}",
+                    cause
+                )
+            }
+            Self::CouldNotCreateRecordingFile(cause) => {
+                write!(f, "Could not create the file for the recording: {}", cause)
+            }
+            Self::WindowCreationFailed(cause) => {
+                write!(f, "Could not create the window: {}", cause)
+            }
+            Self::TextureLoadFailed(cause) => write!(f, "{}", cause),
+            Self::TextureUploadFailed(cause) => {
+                write!(f, "Could not upload the texture: {}", cause)
+            }
+            Self::FontLoadFailed(cause) => write!(f, "{}", cause),
+            Self::InputQueuePoisoned(cause) => {
+                write!(f, "The input queue is poisoned ({})", cause)
+            }
+            Self::LockingFailedInternally(cause) => {
+                write!(f, "Locking failed internally ({})", cause)
+            }
+            Self::CloseTimedOut(timeout) => {
+                write!(f, "The render thread didn't stop within {:?}", timeout)
+            }
+            Self::UnknownGeometryHandle(handle) => {
+                write!(f, "The retained scene contains no geometry {:?}", handle)
+            }
+            Self::PolygonTriangulationFailed(error) => {
+                write!(f, "Could not fill the polygon: {}", error)
+            }
+            Self::FrameReadBackFailed(cause) => {
+                write!(f, "Could not read back the presented frame: {}", cause)
+            }
+        }
     }
 }
 
-impl Error for PistonVisualiserError {}
+impl Error for PistonVisualiserError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::PolygonTriangulationFailed(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Locks the mutex unless another thread holds it, ignoring poisoning like the other locks.
+pub(crate) fn try_lock<T>(mutex: &Mutex<T>) -> Option<MutexGuard<'_, T>> {
+    match mutex.try_lock() {
+        Ok(guard) => Some(guard),
+        Err(TryLockError::Poisoned(poisoned)) => Some(poisoned.into_inner()),
+        Err(TryLockError::WouldBlock) => None,
+    }
+}
+
+/// Extracts the message of a panic, which is a `&str` or a `String` for the usual `panic!` calls.
+pub(crate) fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic payload".to_string()
+    }
+}
 
 /* --- --- --- PistonVisualiserError --- --- --- */
 
+/// Error of rendering an environment.
+///
+/// It only implements `Error` for `'static` environment errors, because `source` returns the
+/// error of the environment as `dyn Error + 'static`.
 #[derive(Debug)]
 pub enum FurtherPistonVisualiserError<DrawableEnvironmentError: Error> {
+    /// The environment couldn't provide its geometries.
     RenderingEnvironmentError(DrawableEnvironmentError),
+    /// A thread panicked while holding a lock shared with the render thread.
     LockingFailedInternally(String),
+    /// The render thread panicked earlier, so nothing can be rendered anymore.
+    RenderThreadPanicked(String),
+    /// The render thread didn't present the frame within the timeout of the lockstep mode.
+    PresentTimedOut(Duration),
+    /// The window was closed before the frame was presented in the lockstep mode.
+    WindowClosedBeforePresent,
 }
 
 impl<DrawableEnvironmentError: Error> Display
     for FurtherPistonVisualiserError<DrawableEnvironmentError>
 {
-    fn fmt(&self, _f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        todo!()
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::RenderingEnvironmentError(error) => {
+                write!(f, "Could not render the environment: {}", error)
+            }
+            Self::LockingFailedInternally(cause) => {
+                write!(f, "Locking failed internally ({})", cause)
+            }
+            Self::RenderThreadPanicked(message) => {
+                write!(f, "The render thread panicked ({})", message)
+            }
+            Self::PresentTimedOut(timeout) => {
+                write!(f, "The frame wasn't presented within {:?}", timeout)
+            }
+            Self::WindowClosedBeforePresent => {
+                write!(f, "The window was closed before the frame was presented")
+            }
+        }
     }
 }
 
-impl<DrawableEnvironmentError: Error> Error
+impl<DrawableEnvironmentError: Error + 'static> Error
     for FurtherPistonVisualiserError<DrawableEnvironmentError>
 {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::RenderingEnvironmentError(error) => Some(error),
+            Self::LockingFailedInternally(_)
+            | Self::RenderThreadPanicked(_)
+            | Self::PresentTimedOut(_)
+            | Self::WindowClosedBeforePresent => None,
+        }
+    }
 }
 
 impl<DrawableEnvironmentError: Error> From<DrawableEnvironmentError>
@@ -89,48 +340,89 @@ impl<DrawableEnvironmentError: Error> From<DrawableEnvironmentError>
 
 /* --- --- --- PistonVisualiserInputProvider --- --- --- */
 
+/// Input together with its position in the coordinates of the environment.
+type PositionedInput = (Input, Option<EnvironmentPosition>);
+
+/// Queue of the inputs of the window.
+///
+/// Mouse and touch inputs are additionally stored with their position in the coordinates of the
+/// environment, which can be retrieved with `pop_with_position` and `pop_all_with_positions`.
+/// Mouse buttons get the position of the last cursor movement.
 #[derive(Default)]
 pub struct PistonVisualiserInputProvider {
-    input_queue: Arc<Mutex<VecDeque<Input>>>,
+    input_queue: Arc<Mutex<VecDeque<PositionedInput>>>,
+    pick_event_queue: Arc<Mutex<VecDeque<PickEvent>>>,
 }
 
 impl PistonVisualiserInputProvider {
-    fn push_back(&mut self, input: Input) {
+    fn push_back(&mut self, input: Input, position: Option<EnvironmentPosition>) {
+        self.lock_input_queue().push_back((input, position));
+    }
+
+    fn push_pick_event(&mut self, pick_event: PickEvent) {
+        self.pick_event_queue
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push_back(pick_event);
+    }
+
+    /// Returns and removes the pick events, which are only pushed if enabled with
+    /// `PistonVisualiserSettings::pick_events`. They are queued separately from the inputs.
+    pub fn pop_all_pick_events(&mut self) -> Vec<PickEvent> {
+        self.pick_event_queue
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .drain(..)
+            .collect()
+    }
+
+    /// The queue only holds inputs, so it stays usable even if a thread panicked while using it.
+    fn lock_input_queue(&self) -> MutexGuard<'_, VecDeque<PositionedInput>> {
         self.input_queue
             .lock()
-            .expect("Could not unwrap input_queue in PistonVisualiserInputProvider!")
-            .push_back(input);
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Like `pop_all`, but fails if a thread panicked while using the input queue.
+    pub fn try_pop_all(&mut self) -> Result<Vec<Input>, PistonVisualiserError> {
+        self.input_queue
+            .lock()
+            .map(|mut input_queue| input_queue.drain(..).map(|(input, _)| input).collect())
+            .map_err(|e| PistonVisualiserError::InputQueuePoisoned(format!("{}", e)))
+    }
+
+    /// Like `pop`, but additionally returns the position in the coordinates of the environment
+    /// for mouse and touch inputs.
+    pub fn pop_with_position(&mut self) -> Option<(Input, Option<EnvironmentPosition>)> {
+        self.lock_input_queue().pop_front()
+    }
+
+    /// Like `pop_all`, but additionally returns the positions in the coordinates of the
+    /// environment for mouse and touch inputs.
+    pub fn pop_all_with_positions(&mut self) -> Vec<(Input, Option<EnvironmentPosition>)> {
+        self.lock_input_queue().drain(..).collect()
     }
 }
 
 impl InputProvider for PistonVisualiserInputProvider {
     fn clear(&mut self) {
-        self.input_queue
-            .lock()
-            .expect("Could not unwrap input_queue in PistonVisualiserInputProvider!")
-            .clear();
+        self.lock_input_queue().clear();
     }
 
     fn peek(&self) -> Option<Input> {
-        self.input_queue
-            .lock()
-            .expect("Could not unwrap input_queue in PistonVisualiserInputProvider!")
+        self.lock_input_queue()
             .front()
-            .cloned()
+            .map(|(input, _)| input.clone())
     }
 
     fn pop(&mut self) -> Option<Input> {
-        self.input_queue
-            .lock()
-            .expect("Could not unwrap input_queue in PistonVisualiserInputProvider!")
-            .pop_front()
+        self.lock_input_queue().pop_front().map(|(input, _)| input)
     }
 
     fn pop_all(&mut self) -> Vec<Input> {
-        self.input_queue
-            .lock()
-            .expect("Could not unwrap input_queue in PistonVisualiserInputProvider!")
+        self.lock_input_queue()
             .drain(..)
+            .map(|(input, _)| input)
             .collect()
     }
 }
@@ -139,26 +431,137 @@ impl Clone for PistonVisualiserInputProvider {
     fn clone(&self) -> Self {
         Self {
             input_queue: Arc::clone(&self.input_queue),
+            pick_event_queue: Arc::clone(&self.pick_event_queue),
         }
     }
 }
 
+/* --- --- --- Diagnostics --- --- --- */
+
+/// Queue of errors the render thread recovered from, limited to the latest ones if nobody takes
+/// them.
+#[derive(Clone, Default)]
+pub(crate) struct Diagnostics {
+    queue: Arc<Mutex<VecDeque<PistonVisualiserError>>>,
+}
+
+impl Diagnostics {
+    const CAPACITY: usize = 64;
+
+    pub fn push(&self, error: PistonVisualiserError) {
+        if let Ok(mut queue) = self.queue.lock() {
+            while queue.len() >= Self::CAPACITY {
+                let _ = queue.pop_front();
+            }
+            queue.push_back(error);
+        }
+    }
+
+    pub fn take(&self) -> Vec<PistonVisualiserError> {
+        self.queue
+            .lock()
+            .map(|mut queue| queue.drain(..).collect())
+            .unwrap_or_default()
+    }
+}
+
+/* --- --- --- ThreadFinished --- --- --- */
+
+/// Flag which is set as soon as the render thread function returned or panicked, which can be
+/// waited for with a timeout unlike `JoinHandle::join`.
+#[derive(Clone, Default)]
+struct ThreadFinished {
+    finished: Arc<(Mutex<bool>, Condvar)>,
+}
+
+impl ThreadFinished {
+    pub fn set(&self) {
+        let (finished, condvar) = &*self.finished;
+        (*finished.lock().unwrap_or_else(PoisonError::into_inner)) = true;
+        condvar.notify_all();
+    }
+
+    /// Returns whether the thread finished in time.
+    pub fn wait_timeout(&self, timeout: Duration) -> bool {
+        let (finished, condvar) = &*self.finished;
+        let guard = finished.lock().unwrap_or_else(PoisonError::into_inner);
+        let (guard, _) = condvar
+            .wait_timeout_while(guard, timeout, |finished| !*finished)
+            .unwrap_or_else(PoisonError::into_inner);
+        *guard
+    }
+}
+
+/* --- --- --- PresentedFrames --- --- --- */
+
+/// Id of the last frame the render thread presented, which can be waited for in lockstep mode.
+#[derive(Clone, Default)]
+struct PresentedFrames {
+    presented: Arc<(Mutex<u64>, Condvar)>,
+}
+
+impl PresentedFrames {
+    pub fn set(&self, frame: u64) {
+        let (presented, condvar) = &*self.presented;
+        let mut presented = presented.lock().unwrap_or_else(PoisonError::into_inner);
+        (*presented) = (*presented).max(frame);
+        condvar.notify_all();
+    }
+
+    /// Waits until `frame` was presented, but at most for `timeout`. Waiting stops early as soon
+    /// as `is_alive` returns `false`.
+    pub fn wait_for<F: Fn() -> bool>(
+        &self,
+        frame: u64,
+        timeout: Option<Duration>,
+        is_alive: F,
+    ) -> AliveWait {
+        let (presented, condvar) = &*self.presented;
+        wait_while_alive(
+            condvar,
+            presented.lock().unwrap_or_else(PoisonError::into_inner),
+            timeout,
+            |presented| *presented < frame,
+            is_alive,
+        )
+    }
+}
+
 /* --- --- --- TextureBuffer --- --- --- */
 
 struct TextureBuffer {
-    starting_uses: usize,
-    buffered_textures: HashMap<TextureSource, (usize, G2dTexture)>,
+    /// `None` keeps the textures forever.
+    starting_uses: Option<usize>,
+    buffered_textures: HashMap<TextureSource, (usize, Option<G2dTexture>)>,
+    loaded_textures: usize,
+    evicted_textures: usize,
 }
 
 impl TextureBuffer {
-    pub fn new(starting_uses: usize) -> Self {
+    pub fn new(texture_cache_policy: TextureCachePolicy) -> Self {
         Self {
-            starting_uses: starting_uses.max(1),
+            starting_uses: match texture_cache_policy {
+                TextureCachePolicy::DropUnusedAfterFrames(frames) => Some(frames.max(1)),
+                TextureCachePolicy::KeepForever => None,
+            },
             buffered_textures: HashMap::default(),
+            loaded_textures: 0,
+            evicted_textures: 0,
         }
     }
 
+    /// Returns and resets the number of textures loaded and evicted since the last call.
+    pub fn take_counts(&mut self) -> (usize, usize) {
+        let counts = (self.loaded_textures, self.evicted_textures);
+        self.loaded_textures = 0;
+        self.evicted_textures = 0;
+        counts
+    }
+
     pub fn decrease_and_drop(&mut self) {
+        if self.starting_uses.is_none() {
+            return;
+        }
         self.buffered_textures
             .iter_mut()
             .for_each(|(_, (counter, _))| {
@@ -172,77 +575,742 @@ impl TextureBuffer {
             .filter(|(_, (counter, _))| *counter == 0)
             .map(|(texture_source, _)| texture_source.clone())
             .collect::<Vec<TextureSource>>();
+        self.evicted_textures += m.len();
         m.iter().for_each(|texture_source| {
             let _ = self.buffered_textures.remove(texture_source);
         });
     }
 
-    pub fn load_or_mark_use(&mut self, texture_source: TextureSource, window: &mut PistonWindow) {
-        if self.buffered_textures.contains_key(&texture_source) {
-            let (counter, _) = self.buffered_textures.get_mut(&texture_source).unwrap();
+    /// Loads the texture if it isn't buffered yet. If loading fails a placeholder is buffered
+    /// instead, and if uploading fails nothing is, so the errors are only reported once and not
+    /// on every frame.
+    pub fn load_or_mark_use(
+        &mut self,
+        texture_source: &TextureSource,
+        window: &mut PistonWindow,
+        diagnostics: &Diagnostics,
+    ) {
+        if let Some((counter, _)) = self.buffered_textures.get_mut(texture_source) {
             (*counter) += 1;
-        } else {
-            let loaded = match &texture_source {
-                TextureSource::Path(path) => Texture::from_path(
-                    &mut window.create_texture_context(),
-                    path,
-                    Flip::None,
-                    &TextureSettings::new(),
-                )
-                .unwrap_or_else(|error| {
-                    panic!("Could not load {} as texture (cause: {})", path, error)
-                }),
-                TextureSource::Bytes {
-                    data,
-                    width,
-                    height,
-                } => Texture::from_image(
-                    &mut window.create_texture_context(),
-                    &ImageBuffer::from_vec(*width, *height, data.clone()).unwrap(),
-                    &TextureSettings::new(),
-                )
-                .unwrap_or_else(|error| {
-                    panic!(
-                        "Could not load texture from bytes with size {}x{} (cause: {})",
-                        width, height, error
-                    )
-                }),
-            };
-            let _ = self
-                .buffered_textures
-                .insert(texture_source, (self.starting_uses, loaded));
+            return;
         }
+        let image = load_texture_image(texture_source).unwrap_or_else(|error| {
+            diagnostics.push(PistonVisualiserError::TextureLoadFailed(error));
+            placeholder_texture_image(texture_source)
+        });
+        let loaded = Texture::from_image(
+            &mut window.create_texture_context(),
+            &image,
+            &TextureSettings::new(),
+        )
+        .map_err(|error| {
+            diagnostics.push(PistonVisualiserError::TextureUploadFailed(
+                error.to_string(),
+            ))
+        })
+        .ok();
+        self.loaded_textures += 1;
+        let _ = self.buffered_textures.insert(
+            texture_source.clone(),
+            (self.starting_uses.unwrap_or(1), loaded),
+        );
     }
 
+    /// Returns `None` for textures which couldn't even be replaced by a placeholder.
     pub fn get(&self, texture_source: &TextureSource) -> Option<&G2dTexture> {
         if let Some((_, texture)) = self.buffered_textures.get(texture_source) {
-            Some(texture)
+            texture.as_ref()
         } else {
             None
         }
     }
 }
 
+/// Loads the texture into memory, like `Texture::from_path` but without uploading it.
+pub(crate) fn load_texture_image(texture_source: &TextureSource) -> Result<RgbaImage, String> {
+    match texture_source {
+        TextureSource::Path(path) => image::open(path)
+            .map(|image| image.to_rgba8())
+            .map_err(|error| format!("Could not load {} as texture (cause: {})", path, error)),
+        TextureSource::Bytes {
+            data,
+            width,
+            height,
+        } => RgbaImage::from_raw(*width, *height, data.clone()).ok_or_else(|| {
+            format!(
+                "Could not load texture from bytes with size {}x{} (cause: expected {} bytes, got {})",
+                width,
+                height,
+                (*width as usize) * (*height as usize) * 4,
+                data.len()
+            )
+        }),
+    }
+}
+
+/// Magenta and black checkerboard, which is drawn instead of textures that couldn't be loaded.
+pub(crate) fn placeholder_texture_image(texture_source: &TextureSource) -> RgbaImage {
+    let (width, height) = match texture_source {
+        TextureSource::Bytes { width, height, .. } if *width > 0 && *height > 0 => {
+            (*width, *height)
+        }
+        _ => (64, 64),
+    };
+    RgbaImage::from_fn(width, height, |x, y| {
+        if (x / 8 + y / 8) % 2 == 0 {
+            image::Rgba([255, 0, 255, 255])
+        } else {
+            image::Rgba([0, 0, 0, 255])
+        }
+    })
+}
+
+/* --- --- --- GlyphBuffer --- --- --- */
+
+#[derive(Default)]
+struct GlyphBuffer {
+    /// Fonts which couldn't be loaded are kept as `None`, so loading isn't retried every frame.
+    buffered_glyphs: HashMap<FontSource, Option<Glyphs>>,
+}
+
+impl GlyphBuffer {
+    pub fn load(
+        &mut self,
+        font_source: &FontSource,
+        window: &mut PistonWindow,
+    ) -> Result<(), PistonVisualiserError> {
+        if self.buffered_glyphs.contains_key(font_source) {
+            return Ok(());
+        }
+        let loaded = match font_source {
+            FontSource::Path(path) => window
+                .load_font(path)
+                .map_err(|error| format!("Could not load {} as font (cause: {})", path, error)),
+            _ => Glyphs::from_bytes(
+                font_source.bytes().unwrap(),
+                window.create_texture_context(),
+                TextureSettings::new(),
+            )
+            .map_err(|_| "Could not load font from bytes".to_string()),
+        };
+        let (loaded, result) = match loaded {
+            Ok(glyphs) => (Some(glyphs), Ok(())),
+            Err(cause) => (None, Err(PistonVisualiserError::FontLoadFailed(cause))),
+        };
+        let _ = self.buffered_glyphs.insert(font_source.clone(), loaded);
+        result
+    }
+
+    pub fn get_mut(&mut self, font_source: &FontSource) -> Option<&mut Glyphs> {
+        self.buffered_glyphs
+            .get_mut(font_source)
+            .and_then(Option::as_mut)
+    }
+
+    pub fn flush(&mut self, device: &mut Device) {
+        self.buffered_glyphs
+            .values_mut()
+            .flatten()
+            .for_each(|glyphs| glyphs.factory.encoder.flush(device));
+    }
+}
+
+/* --- --- --- RenderLoop --- --- --- */
+
+/// Geometries and texts of the current data, transformed into the viewport of the window.
+struct TransformedData {
+    viewport_rect: [i32; 4],
+    camera: Camera,
+    draw_state: DrawState,
+    geometry_2ds: Vec<Geometry2D>,
+    /// Triangulated polygons of the geometries, which don't depend on the viewport or camera.
+    polygon_fills: PolygonFills,
+    texts: Vec<Text2D>,
+}
+
+impl TransformedData {
+    /// Returns the cached data, after transforming it anew if the viewport or camera changed.
+    /// The polygons are only triangulated if there's no cached data, i.e. once per frame.
+    fn update<'a>(
+        cache: &'a mut Option<Self>,
+        data: &PistonVisualiserSyncedData,
+        viewport_rect: [i32; 4],
+        camera: Camera,
+        diagnostics: &Diagnostics,
+    ) -> &'a Self {
+        if cache.as_ref().map_or(true, |transformed| {
+            transformed.viewport_rect != viewport_rect || transformed.camera != camera
+        }) {
+            let polygon_fills = match cache.take() {
+                Some(transformed) => transformed.polygon_fills,
+                None => triangulate_polygons(&data.geometry_2ds, diagnostics),
+            };
+            *cache = Some(Self::of(data, viewport_rect, camera, polygon_fills));
+        }
+        cache.as_ref().unwrap()
+    }
+
+    fn of(
+        data: &PistonVisualiserSyncedData,
+        viewport_rect: [i32; 4],
+        camera: Camera,
+        polygon_fills: PolygonFills,
+    ) -> Self {
+        let (scissor, transform) = PistonVisualiser::camera_view_transformation(
+            &data.preferred_view,
+            viewport_rect,
+            camera,
+        );
+        Self {
+            viewport_rect,
+            camera,
+            draw_state: if let Some(scissor) = scissor {
+                DrawState::default().scissor(scissor)
+            } else {
+                DrawState::default()
+            },
+            geometry_2ds: data
+                .geometry_2ds
+                .iter()
+                .map(|geometry_2d| geometry_2d.clone().append_transformation(transform.clone()))
+                .collect(),
+            polygon_fills,
+            texts: data
+                .texts
+                .iter()
+                .map(|text| text.clone().append_transformation(transform.clone()))
+                .collect(),
+        }
+    }
+}
+
+/// Triangulates the polygons among the geometries and reports those which can't be filled.
+pub(crate) fn triangulate_polygons(
+    geometry_2ds: &[Geometry2D],
+    diagnostics: &Diagnostics,
+) -> PolygonFills {
+    let (polygon_fills, errors) = PolygonFills::of(geometry_2ds);
+    errors.into_iter().for_each(|error| {
+        diagnostics.push(PistonVisualiserError::PolygonTriangulationFailed(error))
+    });
+    polygon_fills
+}
+
+/// The window together with everything needed to draw into it, which is driven either by the
+/// render thread of a `PistonVisualiser` or by the caller of a `PumpedPistonVisualiser`.
+pub(crate) struct RenderLoop {
+    pub window: PistonWindow,
+    settings: PistonVisualiserSettings,
+    data: PistonVisualiserSyncedData,
+    /// Transformed current data, which is only updated when the data or the viewport changes.
+    transformed: Option<TransformedData>,
+    /// Drawn beneath the current data.
+    pub scene: RetainedScene,
+    pub camera_controller: CameraController,
+    /// Last position of the mouse cursor in the window.
+    cursor: Option<[f64; 2]>,
+    /// Geometry under the cursor, only tracked for pick events.
+    hovered: Option<PickedGeometry>,
+    texture_buffer: TextureBuffer,
+    glyph_buffer: GlyphBuffer,
+    last_draw: Option<Instant>,
+    /// Time the environment took to provide the current data.
+    pub environment_duration: Option<Duration>,
+    pub profile: Option<FrameProfile>,
+    pub show_profiling_overlay: bool,
+    /// Line shown in the top right corner of the window, e.g. while paused.
+    pub status_line: Option<String>,
+}
+
+impl RenderLoop {
+    pub fn new(settings: PistonVisualiserSettings) -> Result<Self, String> {
+        let mut window: PistonWindow =
+            WindowSettings::new(settings.window_title.as_str(), settings.window_dimension)
+                .exit_on_esc(settings.exit_on_esc)
+                .resizable(settings.resizable)
+                .vsync(settings.vsync)
+                .samples(settings.samples)
+                .decorated(settings.decorated)
+                .fullscreen(settings.fullscreen)
+                .build()
+                .map_err(|error| format!("{}", error))?;
+        window.set_ups(0);
+        if let Some(some_max_frames_per_second) = settings.max_frames_per_second {
+            window.set_max_fps(some_max_frames_per_second);
+        }
+        Ok(Self {
+            window,
+            texture_buffer: TextureBuffer::new(settings.texture_cache_policy),
+            glyph_buffer: GlyphBuffer::default(),
+            data: PistonVisualiserSyncedData::default(),
+            transformed: None,
+            scene: RetainedScene::default(),
+            camera_controller: CameraController::default(),
+            cursor: None,
+            hovered: None,
+            last_draw: None,
+            environment_duration: None,
+            profile: None,
+            show_profiling_overlay: settings.profiling_overlay,
+            status_line: None,
+            settings,
+        })
+    }
+
+    pub fn set_data(&mut self, data: PistonVisualiserSyncedData) {
+        self.data = data;
+        self.transformed = None;
+        if self.data.background_color.is_none() {
+            self.data.background_color = self.settings.background_color;
+        }
+    }
+
+    /// Draws the current data on render events and pushes inputs into the `input_provider`.
+    /// Returns whether a frame was drawn.
+    pub fn handle_event(
+        &mut self,
+        event: &Event,
+        input_provider: &mut PistonVisualiserInputProvider,
+        diagnostics: &Diagnostics,
+    ) -> bool {
+        match event {
+            Event::Loop(Loop::Render(_)) => {
+                self.draw(event, diagnostics);
+                true
+            }
+            Event::Input(input_args, _) => {
+                if let (piston_window::Input::Resize(_), Some((min_width, min_height))) =
+                    (input_args, self.settings.minimum_size)
+                {
+                    let size = self.window.size();
+                    if size.width < min_width as f64 || size.height < min_height as f64 {
+                        self.window.set_size((
+                            size.width.max(min_width as f64),
+                            size.height.max(min_height as f64),
+                        ));
+                    }
+                }
+                if self.settings.interactive_camera {
+                    let size = self.window.size();
+                    self.camera_controller
+                        .handle_input(input_args, [size.width, size.height]);
+                }
+                let position = self.environment_position_of(input_args);
+                let input = PistonVisualiser::map_piston_input_to(input_args);
+                if self.settings.pick_events {
+                    self.push_pick_event(&input, input_provider);
+                }
+                input_provider.push_back(input, position);
+                false
+            }
+            _ => false,
+        }
+    }
+
+    /// Pushes a pick event if the input changes the hovered geometry or presses a mouse button.
+    fn push_pick_event(
+        &mut self,
+        input: &Input,
+        input_provider: &mut PistonVisualiserInputProvider,
+    ) {
+        match input {
+            Input::Move(Motion::MouseCursor(cursor)) => {
+                let hovered = self.pick_at(*cursor);
+                if hovered != self.hovered {
+                    self.hovered = hovered.clone();
+                    input_provider.push_pick_event(PickEvent::Hovered(hovered));
+                }
+            }
+            Input::Button(ButtonArgs {
+                state: ButtonState::Press,
+                button: Button::Mouse(button),
+                ..
+            }) => {
+                if let Some(cursor) = self.cursor {
+                    input_provider.push_pick_event(PickEvent::Clicked {
+                        button: *button,
+                        geometry: self.pick_at(cursor),
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns the position and lines of the tooltip for the geometry under the cursor.
+    fn tooltip(&self) -> Option<([f64; 2], Vec<String>)> {
+        const CURSOR_OFFSET: f64 = 16f64;
+        if !self.settings.tooltips || self.data.metadata.is_empty() {
+            return None;
+        }
+        let cursor = self.cursor?;
+        let metadata = match self.pick_at(cursor)? {
+            PickedGeometry::Rendered(path) => self.data.metadata.get(path[0])?,
+            PickedGeometry::Scene { .. } => return None,
+        };
+        if metadata.is_empty() {
+            return None;
+        }
+        Some((
+            [cursor[0] + CURSOR_OFFSET, cursor[1] + CURSOR_OFFSET],
+            metadata
+                .iter()
+                .map(|(key, value)| format!("{}: {}", key, value))
+                .collect(),
+        ))
+    }
+
+    /// Finds the top-most geometry drawn at the position in pixels of the window.
+    fn pick_at(&self, window_position: [f64; 2]) -> Option<PickedGeometry> {
+        let size = self.window.size();
+        let draw_size = self.window.draw_size();
+        let outer = picking::window_pixel_matrix([size.width, size.height]);
+        let point_outer = picking::point_pixel_matrix([draw_size.width, draw_size.height]);
+        if let Some(path) = self.transformed.as_ref().and_then(|transformed| {
+            picking::pick_with(
+                &transformed.geometry_2ds,
+                window_position,
+                picking::PICK_TOLERANCE_PIXELS,
+                &outer,
+                &point_outer,
+            )
+        }) {
+            return Some(PickedGeometry::Rendered(path));
+        }
+        self.scene
+            .transformed_geometries()
+            .rev()
+            .find_map(|(handle, geometry, _)| {
+                picking::pick_with(
+                    std::slice::from_ref(geometry),
+                    window_position,
+                    picking::PICK_TOLERANCE_PIXELS,
+                    &outer,
+                    &point_outer,
+                )
+                .map(|path| PickedGeometry::Scene {
+                    handle,
+                    path: path[1..].to_vec(),
+                })
+            })
+    }
+
+    /// Maps the position of mouse and touch inputs into the coordinates of the environment, as
+    /// currently drawn.
+    fn environment_position_of(
+        &mut self,
+        input: &piston_window::Input,
+    ) -> Option<EnvironmentPosition> {
+        let size = self.window.size();
+        let window_size = [size.width, size.height];
+        let window_position = match input {
+            piston_window::Input::Move(piston_window::Motion::MouseCursor(cursor)) => {
+                self.cursor = Some(*cursor);
+                *cursor
+            }
+            piston_window::Input::Move(piston_window::Motion::Touch(touch_args)) => {
+                let position = touch_args.position();
+                [position[0] * window_size[0], position[1] * window_size[1]]
+            }
+            piston_window::Input::Button(piston_window::ButtonArgs {
+                button: piston_window::Button::Mouse(_),
+                ..
+            }) => self.cursor?,
+            _ => return None,
+        };
+        Some(pointer::environment_position(
+            window_position,
+            window_size,
+            &self.data.preferred_view,
+            &self.camera_controller.camera,
+        ))
+    }
+
+    /// Handles all pending events of the window without waiting or drawing.
+    pub fn pump_events(
+        &mut self,
+        input_provider: &mut PistonVisualiserInputProvider,
+        diagnostics: &Diagnostics,
+    ) {
+        while let Some(event) = self.window.poll_event() {
+            // Lets the window resize its buffers, like `PistonWindow::next` does.
+            self.window.event(&event);
+            let _ = self.handle_event(&event, input_provider, diagnostics);
+        }
+    }
+
+    /// Draws the current data and swaps the buffers, like the event loop does for render events.
+    pub fn present(&mut self, diagnostics: &Diagnostics) {
+        let window_size = self.window.size();
+        let draw_size = self.window.draw_size();
+        let event = Event::Loop(Loop::Render(RenderArgs {
+            ext_dt: 0f64,
+            window_size: [window_size.width, window_size.height],
+            draw_size: [draw_size.width as u32, draw_size.height as u32],
+        }));
+        self.draw(&event, diagnostics);
+        self.window.swap_buffers();
+        self.window
+            .event(&Event::Loop(Loop::AfterRender(AfterRenderArgs)));
+    }
+
+    pub fn read_back_frame(&mut self) -> Result<RgbaImage, PistonVisualiserError> {
+        let draw_size = self.window.draw_size();
+        capture::read_back_frame(
+            &mut self.window.device,
+            draw_size.width as u32,
+            draw_size.height as u32,
+        )
+    }
+
+    fn draw(&mut self, event: &Event, diagnostics: &Diagnostics) {
+        let started = Instant::now();
+        let mut profile = FrameProfile {
+            frame_interval: self
+                .last_draw
+                .map(|last_draw| started - last_draw)
+                .unwrap_or_default(),
+            environment: self.environment_duration,
+            ..FrameProfile::default()
+        };
+        self.last_draw = Some(started);
+
+        PistonVisualiser::update_texture_buffer(
+            &mut self.texture_buffer,
+            &self.data.geometry_2ds,
+            &mut self.window,
+            diagnostics,
+        );
+        for geometry in self.scene.geometries() {
+            PistonVisualiser::update_texture_buffer(
+                &mut self.texture_buffer,
+                std::slice::from_ref(geometry),
+                &mut self.window,
+                diagnostics,
+            );
+        }
+        profile.texture_update = started.elapsed();
+
+        // Transforms the data before drawing, so the tooltip picks the geometries as drawn now.
+        let transform_started = Instant::now();
+        let camera = self.camera_controller.camera;
+        if let Event::Loop(Loop::Render(render_args)) = event {
+            // Same rectangle as the viewport of the context in `draw_2d`.
+            let viewport_rect = [
+                0,
+                0,
+                render_args.draw_size[0] as i32,
+                render_args.draw_size[1] as i32,
+            ];
+            self.scene.prepare(viewport_rect, camera);
+            TransformedData::update(
+                &mut self.transformed,
+                &self.data,
+                viewport_rect,
+                camera,
+                diagnostics,
+            );
+        }
+        let tooltip = self.tooltip();
+        let transform = transform_started.elapsed();
+
+        let fonts_started = Instant::now();
+        PistonVisualiser::update_glyph_buffer(
+            &mut self.glyph_buffer,
+            &self.data.texts,
+            &mut self.window,
+            diagnostics,
+        );
+        if self.show_profiling_overlay || tooltip.is_some() || self.status_line.is_some() {
+            if let Err(error) = self
+                .glyph_buffer
+                .load(&FontSource::Default, &mut self.window)
+            {
+                diagnostics.push(error);
+            }
+        }
+        profile.font_update = fonts_started.elapsed();
+
+        // The overlay shows the previous frame, because the current one isn't finished yet.
+        let overlay_lines = if self.show_profiling_overlay {
+            self.profile.map(|profile| profile.overlay_lines())
+        } else {
+            None
+        };
+        let data = &self.data;
+        let transformed = &self.transformed;
+        let scene = &self.scene;
+        let status_line = &self.status_line;
+        let texture_buffer = &self.texture_buffer;
+        let glyph_buffer = &mut self.glyph_buffer;
+        let mut geometry = Duration::default();
+        let draw_started = Instant::now();
+        self.window.draw_2d(event, |context, graphics, device| {
+            let geometry_started = Instant::now();
+            if let Some(c) = data.background_color {
+                piston_window::clear(c.float_array(), graphics);
+            }
+            PistonVisualiser::render_scene(&context, graphics, device, scene, texture_buffer);
+            if let Some(transformed) = transformed {
+                PistonVisualiser::render(
+                    &context,
+                    graphics,
+                    device,
+                    transformed,
+                    texture_buffer,
+                    glyph_buffer,
+                );
+            }
+            if let Some(overlay_lines) = &overlay_lines {
+                PistonVisualiser::render_text_box(
+                    &context,
+                    graphics,
+                    overlay_lines,
+                    [0f64, 0f64],
+                    glyph_buffer,
+                );
+            }
+            if let Some(status_line) = status_line {
+                PistonVisualiser::render_text_box(
+                    &context,
+                    graphics,
+                    std::slice::from_ref(status_line),
+                    [context.viewport.unwrap().window_size[0], 0f64],
+                    glyph_buffer,
+                );
+            }
+            if let Some((position, lines)) = &tooltip {
+                PistonVisualiser::render_text_box(
+                    &context,
+                    graphics,
+                    lines,
+                    *position,
+                    glyph_buffer,
+                );
+            }
+            geometry = geometry_started.elapsed();
+            glyph_buffer.flush(device);
+        });
+        profile.geometry = transform + geometry;
+        profile.submission = draw_started.elapsed().saturating_sub(geometry);
+
+        self.texture_buffer.decrease_and_drop();
+        let (textures_loaded, textures_evicted) = self.texture_buffer.take_counts();
+        profile.textures_loaded = textures_loaded;
+        profile.textures_evicted = textures_evicted;
+        profile.primitives.count(&self.data.geometry_2ds);
+        self.scene
+            .geometries()
+            .for_each(|geometry| profile.primitives.count(std::slice::from_ref(geometry)));
+        profile.primitives.texts = self.data.texts.len();
+        self.profile = Some(profile);
+    }
+
+    /// Adds the time needed to capture the last drawn frame to its profile.
+    pub fn record_capture(&mut self, capture: Duration) {
+        if let Some(profile) = self.profile.as_mut() {
+            profile.capture = capture;
+        }
+    }
+}
+
 /* --- --- --- PistonVisualiser --- --- --- */
 
-type PistonVisualiserSyncedData = (
-    Vec<Geometry2D>,
-    Option<(Viewport2D, Viewport2DModification)>,
-    Option<Color>,
-);
+/// Data of a single frame, the geometries and texts are shared between the frames until they
+/// change.
+#[derive(Default)]
+pub(crate) struct PistonVisualiserSyncedData {
+    pub geometry_2ds: Arc<[Geometry2D]>,
+    pub preferred_view: Option<(Viewport2D, Viewport2DModification)>,
+    pub background_color: Option<Color>,
+    pub texts: Arc<[Text2D]>,
+    /// Metadata of the geometries with the same index.
+    pub metadata: Arc<[GeometryMetadata]>,
+}
+
+/// Data handed over to the render thread.
+#[derive(Default)]
+pub(crate) struct SubmittedFrame {
+    pub id: u64,
+    /// `None` for the data a new render thread starts with, which wasn't submitted by
+    /// `render_two_dimensional`.
+    pub submitted_at: Option<Instant>,
+    /// Time the environment took to provide the data.
+    pub environment_duration: Option<Duration>,
+    pub data: PistonVisualiserSyncedData,
+}
+
+/// Everything the render thread shares with its `PistonVisualiser`.
+struct RenderThreadShared {
+    close_requested: Arc<AtomicBool>,
+    closed: Arc<AtomicBool>,
+    latest_data: TripleBufferOutput<SubmittedFrame>,
+    presented_frames: PresentedFrames,
+    statistics: Arc<Mutex<HandoffStatistics>>,
+    frame_profile: Arc<Mutex<Option<FrameProfile>>>,
+    profiling_overlay: Arc<AtomicBool>,
+    camera: Arc<Mutex<Camera>>,
+    playback: Playback,
+    input_provider: PistonVisualiserInputProvider,
+    frame_sinks: Arc<Mutex<FrameSinks>>,
+    scene_changes: Receiver<SceneChange>,
+    diagnostics: Diagnostics,
+    failure: Arc<Mutex<Option<PistonVisualiserError>>>,
+}
+
+impl Drop for PistonVisualiser {
+    /// Closes the window, but leaves the render thread detached if it doesn't stop in time.
+    fn drop(&mut self) {
+        let _ = self.close_with_timeout(DROP_CLOSE_TIMEOUT);
+    }
+}
+
+/// State of the render thread of a `PistonVisualiser`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum RenderThreadHealth {
+    Running,
+    /// The window was closed regularly, e.g. by the user or `close`, or couldn't be created, in
+    /// which case `last_error` returns the cause.
+    Closed,
+    /// The render thread panicked, contains the panic message.
+    Panicked(String),
+}
 
 pub struct PistonVisualiser {
+    settings: PistonVisualiserSettings,
     join_handle: Option<JoinHandle<()>>,
     close_requested: Arc<AtomicBool>,
     closed: Weak<AtomicBool>,
 
     input_provider: PistonVisualiserInputProvider,
 
-    last_geometries_2d: Vec<Geometry2D>,
+    last_geometries_2d: Arc<[Geometry2D]>,
     last_preferred_view: Option<(Viewport2D, Viewport2DModification)>,
     last_preferred_background_color: Option<Color>,
-
-    latest_data: Arc<Mutex<Option<PistonVisualiserSyncedData>>>,
+    last_texts: Arc<[Text2D]>,
+    last_metadata: Arc<[GeometryMetadata]>,
+    last_frame_generation: Option<u64>,
+
+    /// Copy of the retained scene, to hand it over to a reopened window.
+    scene: BTreeMap<GeometryHandle, Geometry2D>,
+    scene_view: Option<(Viewport2D, Viewport2DModification)>,
+    next_geometry_handle: u64,
+    scene_changes: Sender<SceneChange>,
+
+    latest_data: TripleBufferInput<SubmittedFrame>,
+    submitted_frames: u64,
+    presented_frames: PresentedFrames,
+    statistics: Arc<Mutex<HandoffStatistics>>,
+    frame_profile: Arc<Mutex<Option<FrameProfile>>>,
+    profiling_overlay: Arc<AtomicBool>,
+    camera: Arc<Mutex<Camera>>,
+    playback: Playback,
+
+    frame_sinks: Arc<Mutex<FrameSinks>>,
+    diagnostics: Diagnostics,
+    /// Error which stopped the render thread.
+    failure: Arc<Mutex<Option<PistonVisualiserError>>>,
+    thread_finished: ThreadFinished,
 }
 
 impl PistonVisualiser {
@@ -251,120 +1319,742 @@ impl PistonVisualiser {
         window_dimension: (u32, u32),
         max_frames_per_second: Option<u64>,
     ) -> Self {
-        let arc1_close_requested = Arc::new(AtomicBool::new(false));
-        let arc2_close_requested = Arc::clone(&arc1_close_requested);
+        let settings = PistonVisualiserSettings::new(window_title, window_dimension);
+        Self::run_with_settings(match max_frames_per_second {
+            Some(max_frames_per_second) => settings.max_frames_per_second(max_frames_per_second),
+            None => settings,
+        })
+    }
 
-        let arc1_closed = Arc::new(AtomicBool::new(false));
-        let arc2_closed = Arc::downgrade(&arc1_closed);
+    /// Like `run`, but waits until the window is created and returns an error if that failed.
+    pub fn try_run(
+        window_title: String,
+        window_dimension: (u32, u32),
+        max_frames_per_second: Option<u64>,
+    ) -> Result<Self, PistonVisualiserError> {
+        let settings = PistonVisualiserSettings::new(window_title, window_dimension);
+        Self::try_run_with_settings(match max_frames_per_second {
+            Some(max_frames_per_second) => settings.max_frames_per_second(max_frames_per_second),
+            None => settings,
+        })
+    }
 
-        let arc1_latest_data = Arc::new(Mutex::new(Some((Vec::new(), None, None))));
-        let arc2_latest_data = Arc::clone(&arc1_latest_data);
+    /// Like `run_with_settings`, but waits until the window is created and returns an error if
+    /// that failed.
+    pub fn try_run_with_settings(
+        settings: PistonVisualiserSettings,
+    ) -> Result<Self, PistonVisualiserError> {
+        let (mut visualiser, window_created) = Self::spawn(settings);
+        visualiser.wait_for_window(window_created)?;
+        Ok(visualiser)
+    }
 
-        let input_provider_a = PistonVisualiserInputProvider::default();
-        let input_provider_b = input_provider_a.clone();
+    /// Opens the window in a separate render thread.
+    ///
+    /// If the window can't be created the visualiser is closed right away and `last_error`
+    /// returns `WindowCreationFailed`, use `try_run_with_settings` to wait for this case.
+    pub fn run_with_settings(settings: PistonVisualiserSettings) -> Self {
+        Self::spawn(settings).0
+    }
 
-        Self {
-            join_handle: Some(thread::spawn(move || {
-                Self::thread_function(
-                    window_title,
-                    window_dimension,
-                    max_frames_per_second,
-                    arc1_close_requested,
-                    arc1_closed,
-                    arc1_latest_data,
-                    input_provider_a,
-                )
-            })),
-            close_requested: arc2_close_requested,
-            closed: arc2_closed,
-            input_provider: input_provider_b,
-            last_geometries_2d: Vec::new(),
+    /// Opens a new window with a fresh render thread, if the window was closed, and waits until
+    /// it's created.
+    ///
+    /// The new window shows the last rendered data and its inputs end up in the same
+    /// `PistonVisualiserInputProvider`.
+    pub fn reopen(&mut self) -> Result<(), PistonVisualiserError> {
+        if self.is_open() {
+            return Ok(());
+        }
+        if let Some(join_handle) = self.join_handle.take() {
+            // A panic was already recorded as failure by the render thread itself.
+            let _ = join_handle.join();
+        }
+        let window_created = self.start_render_thread();
+        self.wait_for_window(window_created)
+    }
+
+    fn spawn(settings: PistonVisualiserSettings) -> (Self, Receiver<Result<(), String>>) {
+        let profiling_overlay = settings.profiling_overlay;
+        let mut visualiser = Self {
+            settings,
+            join_handle: None,
+            close_requested: Arc::new(AtomicBool::new(false)),
+            closed: Weak::new(),
+            input_provider: PistonVisualiserInputProvider::default(),
+            last_geometries_2d: Arc::default(),
             last_preferred_view: None,
             last_preferred_background_color: None,
-            latest_data: arc2_latest_data,
+            last_texts: Arc::default(),
+            last_metadata: Arc::default(),
+            last_frame_generation: None,
+            scene: BTreeMap::new(),
+            scene_view: None,
+            next_geometry_handle: 0,
+            scene_changes: mpsc::channel().0,
+            latest_data: triple_buffer().0,
+            submitted_frames: 0,
+            presented_frames: PresentedFrames::default(),
+            statistics: Arc::new(Mutex::new(HandoffStatistics::default())),
+            frame_profile: Arc::new(Mutex::new(None)),
+            profiling_overlay: Arc::new(AtomicBool::new(profiling_overlay)),
+            camera: Arc::new(Mutex::new(Camera::default())),
+            playback: Playback::default(),
+            frame_sinks: Arc::new(Mutex::new(FrameSinks::default())),
+            diagnostics: Diagnostics::default(),
+            failure: Arc::new(Mutex::new(None)),
+            thread_finished: ThreadFinished::default(),
+        };
+        let window_created = visualiser.start_render_thread();
+        (visualiser, window_created)
+    }
+
+    /// Spawns a render thread with the last rendered data, which reports through the returned
+    /// receiver whether the window could be created.
+    fn start_render_thread(&mut self) -> Receiver<Result<(), String>> {
+        let arc1_close_requested = Arc::new(AtomicBool::new(false));
+        self.close_requested = Arc::clone(&arc1_close_requested);
+
+        let arc1_closed = Arc::new(AtomicBool::new(false));
+        self.closed = Arc::downgrade(&arc1_closed);
+        let arc3_closed = Arc::clone(&arc1_closed);
+
+        self.thread_finished = ThreadFinished::default();
+        let thread_finished_in_thread = self.thread_finished.clone();
+
+        let arc1_failure = Arc::new(Mutex::new(None));
+        self.failure = Arc::clone(&arc1_failure);
+
+        let (mut latest_data_input, latest_data_output) = triple_buffer();
+        latest_data_input.publish(SubmittedFrame {
+            id: self.submitted_frames,
+            submitted_at: None,
+            environment_duration: None,
+            data: PistonVisualiserSyncedData {
+                geometry_2ds: Arc::clone(&self.last_geometries_2d),
+                preferred_view: self.last_preferred_view,
+                background_color: self.last_preferred_background_color,
+                texts: Arc::clone(&self.last_texts),
+                metadata: Arc::clone(&self.last_metadata),
+            },
+        });
+        self.latest_data = latest_data_input;
+
+        let (scene_changes_sender, scene_changes_receiver) = mpsc::channel();
+        let _ = scene_changes_sender.send(SceneChange::View(self.scene_view));
+        self.scene.iter().for_each(|(handle, geometry)| {
+            let _ = scene_changes_sender.send(SceneChange::Set(*handle, geometry.clone()));
+        });
+        self.scene_changes = scene_changes_sender;
+
+        let shared = RenderThreadShared {
+            diagnostics: self.diagnostics.clone(),
+            close_requested: arc1_close_requested,
+            closed: arc1_closed,
+            latest_data: latest_data_output,
+            presented_frames: self.presented_frames.clone(),
+            statistics: Arc::clone(&self.statistics),
+            frame_profile: Arc::clone(&self.frame_profile),
+            profiling_overlay: Arc::clone(&self.profiling_overlay),
+            camera: Arc::clone(&self.camera),
+            playback: self.playback.clone(),
+            input_provider: self.input_provider.clone(),
+            frame_sinks: Arc::clone(&self.frame_sinks),
+            scene_changes: scene_changes_receiver,
+            failure: Arc::clone(&arc1_failure),
+        };
+
+        let settings = self.settings.clone();
+        let (window_created_sender, window_created_receiver) = mpsc::channel();
+
+        self.join_handle = Some(thread::spawn(move || {
+            let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                Self::thread_function(settings, shared, window_created_sender)
+            }));
+            thread_finished_in_thread.set();
+            if let Err(payload) = result {
+                if let Ok(mut failure) = arc1_failure.lock() {
+                    (*failure) = Some(PistonVisualiserError::RenderThreadPanicked(panic_message(
+                        payload.as_ref(),
+                    )));
+                }
+                arc3_closed.store(true, std::sync::atomic::Ordering::Relaxed);
+                // Keeps the payload for joining the thread.
+                panic::resume_unwind(payload);
+            }
+        }));
+        window_created_receiver
+    }
+
+    /// Waits for the render thread to report whether the window could be created.
+    fn wait_for_window(
+        &mut self,
+        window_created: Receiver<Result<(), String>>,
+    ) -> Result<(), PistonVisualiserError> {
+        match window_created.recv() {
+            Ok(Ok(())) => Ok(()),
+            Ok(Err(cause)) => {
+                if let Some(join_handle) = self.join_handle.take() {
+                    let _ = join_handle.join();
+                }
+                Err(PistonVisualiserError::WindowCreationFailed(cause))
+            }
+            Err(_) => match self.join_handle.take().map(JoinHandle::join) {
+                Some(Err(payload)) => Err(PistonVisualiserError::RenderThreadPanicked(
+                    panic_message(payload.as_ref()),
+                )),
+                _ => Err(PistonVisualiserError::WindowCreationFailed(
+                    "Render thread stopped before creating the window".to_string(),
+                )),
+            },
+        }
+    }
+
+    pub fn input_provider(&self) -> PistonVisualiserInputProvider {
+        self.input_provider.clone()
+    }
+
+    /// Like `close`, but gives up waiting for the render thread after `timeout`.
+    ///
+    /// After a timeout the close stays requested and `close` or `close_with_timeout` can be
+    /// called again to wait once more.
+    pub fn close_with_timeout(&mut self, timeout: Duration) -> Result<(), PistonVisualiserError> {
+        if self.join_handle.is_none() {
+            return Ok(());
+        }
+        self.close_requested
+            .store(true, std::sync::atomic::Ordering::Relaxed);
+        if self.thread_finished.wait_timeout(timeout) {
+            self.close()
+        } else {
+            Err(PistonVisualiserError::CloseTimedOut(timeout))
+        }
+    }
+
+    pub fn health(&self) -> RenderThreadHealth {
+        match self.last_error() {
+            Some(PistonVisualiserError::RenderThreadPanicked(message)) => {
+                RenderThreadHealth::Panicked(message)
+            }
+            _ if self.is_open() => RenderThreadHealth::Running,
+            _ => RenderThreadHealth::Closed,
+        }
+    }
+
+    /// Returns the error which stopped the render thread, if it didn't stop regularly.
+    pub fn last_error(&self) -> Option<PistonVisualiserError> {
+        self.failure
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Returns and forgets the errors the render thread recovered from since the last call, e.g.
+    /// textures which couldn't be loaded and are drawn as a checkerboard instead.
+    pub fn take_diagnostics(&self) -> Vec<PistonVisualiserError> {
+        self.diagnostics.take()
+    }
+
+    /// Starts writing the presented frames as PNG files and returns a receiver, which gets the
+    /// path (or the error) of every written file.
+    ///
+    /// A capture which is already running is replaced.
+    pub fn start_frame_capture(
+        &mut self,
+        settings: FrameCaptureSettings,
+    ) -> Result<Receiver<Result<CapturedFrame, FrameCaptureError>>, PistonVisualiserError> {
+        std::fs::create_dir_all(settings.directory()).map_err(|e| {
+            PistonVisualiserError::CouldNotCreateCaptureDirectory(format!(
+                "{} ({})",
+                settings.directory().display(),
+                e
+            ))
+        })?;
+        let (sender, receiver) = mpsc::channel();
+        self.frame_sinks
+            .lock()
+            .map_err(|e| PistonVisualiserError::LockingFailedInternally(format!("{}", e)))?
+            .frame_capture = Some(FrameCapture::new(settings, sender));
+        Ok(receiver)
+    }
+
+    pub fn stop_frame_capture(&mut self) -> Result<(), PistonVisualiserError> {
+        self.frame_sinks
+            .lock()
+            .map_err(|e| PistonVisualiserError::LockingFailedInternally(format!("{}", e)))?
+            .frame_capture = None;
+        Ok(())
+    }
+
+    pub fn is_capturing_frames(&self) -> bool {
+        self.frame_sinks
+            .lock()
+            .map(|frame_sinks| frame_sinks.frame_capture.is_some())
+            .unwrap_or(false)
+    }
+
+    /// Starts recording the presented frames into a single GIF or Y4M file and returns a
+    /// receiver, which gets the finished recording (or the error) once the recording is stopped
+    /// or its maximum duration is reached.
+    ///
+    /// A recording which is already running is finished and replaced.
+    pub fn start_episode_recording(
+        &mut self,
+        settings: EpisodeRecorderSettings,
+    ) -> Result<Receiver<Result<RecordedEpisode, EpisodeRecordingError>>, PistonVisualiserError>
+    {
+        let file = std::fs::File::create(settings.path()).map_err(|e| {
+            PistonVisualiserError::CouldNotCreateRecordingFile(format!(
+                "{} ({})",
+                settings.path().display(),
+                e
+            ))
+        })?;
+        let (sender, receiver) = mpsc::channel();
+        self.frame_sinks
+            .lock()
+            .map_err(|e| PistonVisualiserError::LockingFailedInternally(format!("{}", e)))?
+            .episode_recorder = Some(EpisodeRecorder::new(settings, file, sender));
+        Ok(receiver)
+    }
+
+    /// Stops the recording, the file is finished in the background.
+    pub fn stop_episode_recording(&mut self) -> Result<(), PistonVisualiserError> {
+        self.frame_sinks
+            .lock()
+            .map_err(|e| PistonVisualiserError::LockingFailedInternally(format!("{}", e)))?
+            .episode_recorder = None;
+        Ok(())
+    }
+
+    pub fn is_recording_episode(&self) -> bool {
+        self.frame_sinks
+            .lock()
+            .map(|frame_sinks| frame_sinks.episode_recorder.is_some())
+            .unwrap_or(false)
+    }
+
+    /// Renders the geometries and additionally the texts of the environment.
+    pub fn render_extended_two_dimensional<
+        DrawableEnvironmentError: Error,
+        DrawableEnvironment: ExtendedTwoDimensionalDrawableEnvironment<DrawableEnvironmentError>,
+    >(
+        &mut self,
+        drawable_environment: &DrawableEnvironment,
+    ) -> Result<(), FurtherPistonVisualiserError<DrawableEnvironmentError>> {
+        let environment_started = Instant::now();
+        let frame_generation = drawable_environment.frame_generation();
+        let preferred_view = drawable_environment.preferred_view();
+        // The last geometries and texts were transformed with the last preferred view, so they
+        // can only be reused as long as it stays the same.
+        let data = if frame_generation.is_some()
+            && frame_generation == self.last_frame_generation
+            && preferred_view == self.last_preferred_view
+        {
+            PistonVisualiserSyncedData {
+                geometry_2ds: Arc::clone(&self.last_geometries_2d),
+                preferred_view,
+                background_color: drawable_environment.preferred_background_color(),
+                texts: Arc::clone(&self.last_texts),
+                metadata: Arc::clone(&self.last_metadata),
+            }
+        } else {
+            Self::extended_synced_data_of(drawable_environment)?
+        };
+        self.last_frame_generation = frame_generation;
+        self.submit(data, environment_started.elapsed())
+    }
+
+    /// Inserts a geometry into the retained scene, which is drawn beneath the geometries of
+    /// `render_two_dimensional` until it's removed.
+    ///
+    /// The geometry is given in the coordinates of the view set by `set_scene_view`. Only the
+    /// changes of the scene are handed over to the render thread, so geometries which rarely
+    /// change should be inserted here instead of being rendered every step.
+    pub fn insert_geometry(&mut self, geometry: Geometry2D) -> GeometryHandle {
+        let handle = GeometryHandle(self.next_geometry_handle);
+        self.next_geometry_handle += 1;
+        self.set_scene_geometry(handle, geometry);
+        handle
+    }
+
+    /// Replaces the geometry of the retained scene with the given handle.
+    pub fn update_geometry(
+        &mut self,
+        handle: GeometryHandle,
+        geometry: Geometry2D,
+    ) -> Result<(), PistonVisualiserError> {
+        if !self.scene.contains_key(&handle) {
+            return Err(PistonVisualiserError::UnknownGeometryHandle(handle));
+        }
+        self.set_scene_geometry(handle, geometry);
+        Ok(())
+    }
+
+    /// Removes the geometry with the given handle from the retained scene.
+    pub fn remove_geometry(&mut self, handle: GeometryHandle) -> Result<(), PistonVisualiserError> {
+        if self.scene.remove(&handle).is_none() {
+            return Err(PistonVisualiserError::UnknownGeometryHandle(handle));
+        }
+        // Fails only if the render thread stopped, which gets the whole scene when reopened.
+        let _ = self.scene_changes.send(SceneChange::Remove(handle));
+        Ok(())
+    }
+
+    /// Removes all geometries from the retained scene.
+    pub fn clear_scene(&mut self) {
+        self.scene.clear();
+        let _ = self.scene_changes.send(SceneChange::Clear);
+    }
+
+    /// Sets the view the geometries of the retained scene are given in, like the preferred view
+    /// of an environment.
+    pub fn set_scene_view(&mut self, view: Option<(Viewport2D, Viewport2DModification)>) {
+        self.scene_view = view;
+        let _ = self.scene_changes.send(SceneChange::View(view));
+    }
+
+    fn set_scene_geometry(&mut self, handle: GeometryHandle, geometry: Geometry2D) {
+        self.scene.insert(handle, geometry.clone());
+        let _ = self.scene_changes.send(SceneChange::Set(handle, geometry));
+    }
+
+    /// Returns the timings and counts of the last frame drawn by the render thread.
+    pub fn last_frame_profile(&self) -> Option<FrameProfile> {
+        *self
+            .frame_profile
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Shows or hides the profile of the last frame in the top left corner of the window.
+    pub fn set_profiling_overlay(&self, show: bool) {
+        self.profiling_overlay
+            .store(show, std::sync::atomic::Ordering::Relaxed);
+    }
+
+    pub fn is_showing_profiling_overlay(&self) -> bool {
+        self.profiling_overlay
+            .load(std::sync::atomic::Ordering::Relaxed)
+    }
+
+    /// Returns the current zoom and pan of the interactive camera.
+    pub fn camera(&self) -> Camera {
+        *self.camera.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Whether the playback is paused, by the user pressing space or `.` or by `set_paused`.
+    pub fn is_paused(&self) -> bool {
+        self.playback.is_paused()
+    }
+
+    pub fn set_paused(&self, paused: bool) {
+        self.playback.set_paused(paused);
+    }
+
+    /// Blocks as long as the playback is paused, so calling this between the steps of the
+    /// simulation lets the user pause it from the window. Returns after a single step if the
+    /// user pressed `.` and right away if the window is closed.
+    pub fn wait_while_paused(&self) {
+        self.playback.wait_while_paused(|| self.is_open());
+    }
+
+    /// Speed the user requested with `+` and `-`, from 1/16 to 16, where `1.0` is the normal
+    /// speed. The visualiser doesn't throttle by itself, the simulation has to e.g. sleep
+    /// accordingly between its steps.
+    pub fn requested_speed(&self) -> f64 {
+        self.playback.speed()
+    }
+
+    pub fn set_requested_speed(&self, speed: f64) {
+        self.playback.set_speed(speed);
+    }
+
+    /// Returns a snapshot of the counters and timings of handing frames over to the render
+    /// thread. The render thread never waits to write back its counters, so they may lag behind
+    /// by a few events.
+    pub fn statistics(&self) -> HandoffStatistics {
+        *self
+            .statistics
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub fn reset_statistics(&self) {
+        self.update_statistics(|statistics| *statistics = HandoffStatistics::default());
+    }
+
+    fn update_statistics<F: FnOnce(&mut HandoffStatistics)>(&self, update: F) {
+        update(
+            &mut self
+                .statistics
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner),
+        );
+    }
+
+    /// Hands the data over to the render thread. `environment_duration` is the time the
+    /// environment took to provide it.
+    ///
+    /// Data which equals the last submitted one is only counted as deduplicated. Unless the
+    /// geometries and texts were reused by `frame_generation`, this costs a full comparison of
+    /// them with the last frame, see `unchanged`.
+    fn submit<DrawableEnvironmentError: Error>(
+        &mut self,
+        data: PistonVisualiserSyncedData,
+        environment_duration: Duration,
+    ) -> Result<(), FurtherPistonVisualiserError<DrawableEnvironmentError>> {
+        if let Some(PistonVisualiserError::RenderThreadPanicked(message)) = self.last_error() {
+            return Err(FurtherPistonVisualiserError::RenderThreadPanicked(message));
+        }
+        if self.settings.reopen_when_closed
+            && self.join_handle.is_some()
+            && self.health() == RenderThreadHealth::Closed
+        {
+            if let Err(error) = self.reopen() {
+                self.diagnostics.push(error);
+            }
+        }
+        let lockstep_timeout = match self.settings.render_mode {
+            RenderMode::Latest => None,
+            RenderMode::Lockstep { timeout } => Some(timeout),
+        };
+        // In lockstep mode every submission is presented, even if nothing changed.
+        if lockstep_timeout.is_some()
+            || !Self::unchanged(&data.geometry_2ds, &self.last_geometries_2d)
+            || data.preferred_view != self.last_preferred_view
+            || data.background_color != self.last_preferred_background_color
+            || !Self::unchanged(&data.texts, &self.last_texts)
+            || !Self::unchanged(&data.metadata, &self.last_metadata)
+        {
+            self.last_geometries_2d = Arc::clone(&data.geometry_2ds);
+            self.last_preferred_view = data.preferred_view;
+            self.last_preferred_background_color = data.background_color;
+            self.last_texts = Arc::clone(&data.texts);
+            self.last_metadata = Arc::clone(&data.metadata);
+            self.submitted_frames += 1;
+            let replaced = self.latest_data.publish(SubmittedFrame {
+                id: self.submitted_frames,
+                submitted_at: Some(Instant::now()),
+                environment_duration: Some(environment_duration),
+                data,
+            });
+            self.update_statistics(|statistics| {
+                statistics.frames_submitted += 1;
+                if replaced.is_some_and(|frame| frame.submitted_at.is_some()) {
+                    statistics.frames_dropped += 1;
+                }
+            });
+        } else {
+            self.update_statistics(|statistics| {
+                statistics.frames_submitted += 1;
+                statistics.frames_deduplicated += 1;
+            });
+        }
+        if let Some(timeout) = lockstep_timeout {
+            let closed = self.closed.clone();
+            let is_alive = || {
+                closed
+                    .upgrade()
+                    .map(|closed| !closed.load(std::sync::atomic::Ordering::Relaxed))
+                    .unwrap_or(false)
+            };
+            match self
+                .presented_frames
+                .wait_for(self.submitted_frames, timeout, is_alive)
+            {
+                AliveWait::Done => {}
+                AliveWait::TimedOut => {
+                    return Err(FurtherPistonVisualiserError::PresentTimedOut(
+                        timeout.unwrap_or_default(),
+                    ))
+                }
+                AliveWait::Closed => {
+                    return Err(FurtherPistonVisualiserError::WindowClosedBeforePresent)
+                }
+            }
         }
+        Ok(())
     }
 
-    pub fn input_provider(&self) -> PistonVisualiserInputProvider {
-        self.input_provider.clone()
+    /// Compares the shared geometries or texts of two frames, without looking at the single
+    /// elements if both frames share them anyway. Otherwise this is a full `PartialEq`
+    /// comparison, which walks all elements (and groups) if nothing changed.
+    fn unchanged<T: PartialEq>(new: &Arc<[T]>, last: &Arc<[T]>) -> bool {
+        Arc::ptr_eq(new, last) || new == last
     }
 
     fn update_texture_buffer(
         texture_buffer: &mut TextureBuffer,
         geometry_2ds: &[Geometry2D],
         window: &mut PistonWindow,
+        diagnostics: &Diagnostics,
     ) {
-        geometry_2ds.iter().for_each(|geometry| {
-            if let Geometry2D::Image { texture_source, .. } = geometry {
-                texture_buffer.load_or_mark_use(texture_source.clone(), window);
+        geometry_2ds.iter().for_each(|geometry| match geometry {
+            Geometry2D::Image { texture_source, .. } => {
+                texture_buffer.load_or_mark_use(texture_source, window, diagnostics)
+            }
+            Geometry2D::Group(geometries) => {
+                Self::update_texture_buffer(texture_buffer, geometries, window, diagnostics)
             }
+            _ => {}
         });
     }
 
-    fn thread_function(
-        window_title: String,
-        window_dimension: (u32, u32),
-        max_frames_per_second: Option<u64>,
-        close_requested: Arc<AtomicBool>,
-        closed: Arc<AtomicBool>,
-        latest_data: Arc<Mutex<Option<PistonVisualiserSyncedData>>>,
-        input_provider: PistonVisualiserInputProvider,
+    fn update_glyph_buffer(
+        glyph_buffer: &mut GlyphBuffer,
+        texts: &[Text2D],
+        window: &mut PistonWindow,
+        diagnostics: &Diagnostics,
     ) {
-        let mut window: PistonWindow = WindowSettings::new(window_title.as_str(), window_dimension)
-            .exit_on_esc(true)
-            .build()
-            .expect("Failed to build PistonWindow!");
-        window.set_ups(0);
-        if let Some(some_max_frames_per_second) = max_frames_per_second {
-            window.set_max_fps(some_max_frames_per_second);
-        }
-
-        let (mut geometry_2ds, mut preferred_view, mut background_color) = latest_data
-            .lock()
-            .expect("Could not lock latest_data!")
-            .take()
-            .unwrap_or_default();
-
-        let mut input_provider = input_provider;
+        texts.iter().for_each(|text| {
+            if let Err(error) = glyph_buffer.load(&text.font, window) {
+                diagnostics.push(error);
+            }
+        });
+    }
 
-        let mut texture_buffer = TextureBuffer::new(180);
+    fn thread_function(
+        settings: PistonVisualiserSettings,
+        mut shared: RenderThreadShared,
+        window_created: Sender<Result<(), String>>,
+    ) {
+        let mut render_loop = match RenderLoop::new(settings) {
+            Ok(render_loop) => {
+                // Only try_run waits for the handshake.
+                let _ = window_created.send(Ok(()));
+                render_loop
+            }
+            Err(cause) => {
+                // Recorded before signalling, so it's visible no matter whether anybody waits.
+                *shared
+                    .failure
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner) =
+                    Some(PistonVisualiserError::WindowCreationFailed(cause.clone()));
+                shared
+                    .closed
+                    .store(true, std::sync::atomic::Ordering::Relaxed);
+                let _ = window_created.send(Err(cause));
+                return;
+            }
+        };
 
-        while let Some(event) = window.next() {
-            match event {
-                Event::Loop(Loop::Render(_)) => {
-                    Self::update_texture_buffer(&mut texture_buffer, &geometry_2ds, &mut window);
-                    window.draw_2d(&event, |context, graphics, device| {
-                        Self::render(
-                            &context,
-                            graphics,
-                            device,
-                            &geometry_2ds,
-                            &preferred_view,
-                            &background_color,
-                            &texture_buffer,
-                        );
-                    });
-                    texture_buffer.decrease_and_drop();
+        let SubmittedFrame {
+            id: mut current_frame,
+            submitted_at: mut current_submitted_at,
+            environment_duration,
+            data,
+        } = shared.latest_data.take().unwrap_or_default();
+        render_loop.set_data(data);
+        render_loop.environment_duration = environment_duration;
+        shared
+            .scene_changes
+            .try_iter()
+            .for_each(|change| render_loop.scene.apply(change, &shared.diagnostics));
+        // A reopened window keeps the camera of the closed one.
+        let mut camera = *shared.camera.lock().unwrap_or_else(PoisonError::into_inner);
+        render_loop.camera_controller.camera = camera;
+        let mut playback_generation = None;
+        // Statistics recorded since they were last written back, see `write_back_statistics`.
+        let mut pending_statistics = HandoffStatistics::default();
+
+        let mut input_provider = shared.input_provider;
+
+        while let Some(event) = render_loop.window.next() {
+            render_loop.show_profiling_overlay = shared
+                .profiling_overlay
+                .load(std::sync::atomic::Ordering::Relaxed);
+            if let (
+                true,
+                Event::Input(
+                    piston_window::Input::Button(piston_window::ButtonArgs {
+                        state: piston_window::ButtonState::Press,
+                        button: piston_window::Button::Keyboard(key),
+                        ..
+                    }),
+                    _,
+                ),
+            ) = (render_loop.settings.playback_hotkeys, &event)
+            {
+                shared.playback.handle_key(*key);
+            }
+            if playback_generation != Some(shared.playback.generation()) {
+                playback_generation = Some(shared.playback.generation());
+                render_loop.status_line = shared.playback.status();
+            }
+            let drawn = render_loop.handle_event(&event, &mut input_provider, &shared.diagnostics);
+            if render_loop.camera_controller.camera != camera {
+                camera = render_loop.camera_controller.camera;
+                *shared.camera.lock().unwrap_or_else(PoisonError::into_inner) = camera;
+            }
+            if drawn {
+                let capture_started = Instant::now();
+                if let Err(error) = shared
+                    .frame_sinks
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .present(|| render_loop.read_back_frame())
+                {
+                    shared.diagnostics.push(error);
                 }
-                Event::Input(input_args, _) => {
-                    input_provider.push_back(Self::map_piston_input_to(&input_args));
+                render_loop.record_capture(capture_started.elapsed());
+                // Skipped while the visualiser reads the profile, the next frame replaces it anyway.
+                if let Some(mut frame_profile) = try_lock(&shared.frame_profile) {
+                    (*frame_profile) = render_loop.profile;
+                }
+                shared.presented_frames.set(current_frame);
+                if let Some(submitted_at) = current_submitted_at.take() {
+                    pending_statistics.record_presented(submitted_at.elapsed());
                 }
-                _ => {}
             }
-            if close_requested.load(std::sync::atomic::Ordering::Relaxed) {
-                window.set_should_close(true);
-            } else if let Some((new_geometry_2ds, new_preferred_view, new_background_color)) =
-                latest_data
-                    .lock()
-                    .expect("Could not lock latest_data inside while!")
-                    .take()
+            if shared
+                .close_requested
+                .load(std::sync::atomic::Ordering::Relaxed)
             {
-                geometry_2ds = new_geometry_2ds;
-                preferred_view = new_preferred_view;
-                background_color = new_background_color;
+                render_loop.window.set_should_close(true);
+            } else {
+                if let Some(new_frame) = shared.latest_data.take() {
+                    // The current frame is replaced before it was presented.
+                    if current_submitted_at.is_some() {
+                        pending_statistics.frames_dropped += 1;
+                    }
+                    current_frame = new_frame.id;
+                    current_submitted_at = new_frame.submitted_at;
+                    render_loop.environment_duration = new_frame.environment_duration;
+                    render_loop.set_data(new_frame.data);
+                }
+                for change in shared.scene_changes.try_iter() {
+                    render_loop.scene.apply(change, &shared.diagnostics);
+                }
             }
+            Self::write_back_statistics(&shared.statistics, &mut pending_statistics, false);
+        }
+        Self::write_back_statistics(&shared.statistics, &mut pending_statistics, true);
+        shared
+            .closed
+            .store(true, std::sync::atomic::Ordering::Relaxed);
+    }
+
+    /// Adds the statistics recorded by the render thread to the shared ones, but only if there
+    /// are any and, unless `wait`, only if the visualiser doesn't hold the lock right now, so the
+    /// render thread never waits for it.
+    fn write_back_statistics(
+        statistics: &Mutex<HandoffStatistics>,
+        pending_statistics: &mut HandoffStatistics,
+        wait: bool,
+    ) {
+        if *pending_statistics == HandoffStatistics::default() {
+            return;
+        }
+        let statistics = if wait {
+            Some(statistics.lock().unwrap_or_else(PoisonError::into_inner))
+        } else {
+            try_lock(statistics)
+        };
+        if let Some(mut statistics) = statistics {
+            statistics.merge(&std::mem::take(pending_statistics));
         }
-        closed.store(true, std::sync::atomic::Ordering::Relaxed);
     }
 
     fn map_piston_input_to(piston_input: &piston_window::Input) -> Input {
@@ -697,95 +2387,131 @@ impl PistonVisualiser {
         }
     }
 
-    fn render(
+    fn render_scene(
         context: &Context,
         graphics: &mut G2d,
         device: &mut Device,
-        geometry_2ds: &[Geometry2D],
-        preferred_view: &Option<(Viewport2D, Viewport2DModification)>,
-        background_color: &Option<Color>,
+        scene: &RetainedScene,
         texture_buffer: &TextureBuffer,
     ) {
-        if let Some(c) = background_color {
-            piston_window::clear(c.float_array(), graphics);
+        for (_, geometry_2d, polygon_fills) in scene.transformed_geometries() {
+            Self::render_geometry_2d(
+                context,
+                graphics,
+                device,
+                scene.draw_state(),
+                geometry_2d,
+                &mut polygon_fills.iter(),
+                texture_buffer,
+            );
         }
+    }
 
-        let (draw_state, transform) = if let Some((viewport, viewport_mod)) = preferred_view {
-            match viewport_mod {
-                Viewport2DModification::LooseAspectRatio => (
-                    piston_window::DrawState::default(),
-                    Transformation2D::identity(),
-                ),
-                Viewport2DModification::KeepAspectRatio
-                | Viewport2DModification::KeepAspectRatioAndScissorRemains => {
-                    let ctx_vp_rect = context.viewport.unwrap().rect;
-
-                    let mut h = ctx_vp_rect[3] as f64;
-                    let mut w = viewport.size.width / viewport.size.height * h;
-                    if w > ctx_vp_rect[2] as f64 {
-                        w = ctx_vp_rect[2] as f64;
-                        h = viewport.size.height / viewport.size.width * w;
-                    }
-
-                    let t = Transformation2D::composition(
-                        "KeepAspectRatio".to_string(),
-                        vec![
-                            Transformation2D::translation(
-                                Self::window_viewport()
-                                    .center
-                                    .vector_to(&Position2D::zero()),
-                            ),
-                            Transformation2D::scale(
-                                w / ctx_vp_rect[2] as f64,
-                                h / ctx_vp_rect[3] as f64,
-                            ),
-                            Transformation2D::translation(
-                                Position2D::zero().vector_to(&Self::window_viewport().center),
-                            ),
-                        ],
-                    );
-
-                    let draw_state = if *viewport_mod
-                        == Viewport2DModification::KeepAspectRatioAndScissorRemains
-                    {
-                        piston_window::DrawState::default().scissor([
-                            (((ctx_vp_rect[2] as f64) - w) / 2f64) as u32,
-                            (((ctx_vp_rect[3] as f64) - h) / 2f64) as u32,
-                            w as u32,
-                            h as u32,
-                        ])
-                    } else {
-                        piston_window::DrawState::default()
-                    };
-
-                    (draw_state, t)
-                }
-            }
-        } else {
-            (
-                piston_window::DrawState::default(),
-                Transformation2D::identity(),
-            )
-        };
-
-        for geometry_2d in geometry_2ds {
+    fn render(
+        context: &Context,
+        graphics: &mut G2d,
+        device: &mut Device,
+        transformed: &TransformedData,
+        texture_buffer: &TextureBuffer,
+        glyph_buffer: &mut GlyphBuffer,
+    ) {
+        let mut polygon_fills = transformed.polygon_fills.iter();
+        for geometry_2d in &transformed.geometry_2ds {
             Self::render_geometry_2d(
                 context,
                 graphics,
                 device,
-                &draw_state,
-                &geometry_2d.clone().append_transformation(transform.clone()),
+                &transformed.draw_state,
+                geometry_2d,
+                &mut polygon_fills,
                 texture_buffer,
             );
         }
+
+        for text in &transformed.texts {
+            Self::render_text(
+                context,
+                graphics,
+                &transformed.draw_state,
+                text,
+                glyph_buffer,
+            );
+        }
+    }
+
+    /// Draws the lines as white text on a translucent black box at the position in pixels of the
+    /// window, moved left and up as far as needed to stay inside the window.
+    fn render_text_box(
+        context: &Context,
+        graphics: &mut G2d,
+        lines: &[String],
+        position: [f64; 2],
+        glyph_buffer: &mut GlyphBuffer,
+    ) {
+        const FONT_SIZE: u32 = 12;
+        const LINE_HEIGHT: f64 = 16f64;
+        const MARGIN: f64 = 6f64;
+        if let Some(glyphs) = glyph_buffer.get_mut(&FontSource::Default) {
+            let width = lines
+                .iter()
+                .map(|line| glyphs.width(FONT_SIZE, line).unwrap_or(0f64))
+                .fold(0f64, f64::max)
+                + 2f64 * MARGIN;
+            let height = lines.len() as f64 * LINE_HEIGHT + 2f64 * MARGIN;
+            let window_size = context.viewport.unwrap().window_size;
+            let transform = context.transform.trans(
+                position[0].min(window_size[0] - width).max(0f64),
+                position[1].min(window_size[1] - height).max(0f64),
+            );
+            piston_window::rectangle::Rectangle::new([0f32, 0f32, 0f32, 0.6f32]).draw(
+                [0f64, 0f64, width, height],
+                &DrawState::default(),
+                transform,
+                graphics,
+            );
+            for (index, line) in lines.iter().enumerate() {
+                let _ = piston_window::Text::new_color([1f32; 4], FONT_SIZE).draw(
+                    line,
+                    glyphs,
+                    &DrawState::default(),
+                    transform.trans(MARGIN, MARGIN + (index + 1) as f64 * LINE_HEIGHT - 4f64),
+                    graphics,
+                );
+            }
+        }
+    }
+
+    fn render_text(
+        context: &Context,
+        graphics: &mut G2d,
+        draw_state: &DrawState,
+        text: &Text2D,
+        glyph_buffer: &mut GlyphBuffer,
+    ) {
+        if let Some(glyphs) = glyph_buffer.get_mut(&text.font) {
+            let text_width = glyphs.width(text.font_size, &text.text).unwrap_or(0f64);
+            let offset = text.anchor.offset(text_width, text.font_size);
+            let position = text.pixel_position(context.viewport.unwrap().window_size);
+            let _ = piston_window::Text::new_color(text.color.float_array(), text.font_size).draw(
+                &text.text,
+                glyphs,
+                draw_state,
+                context
+                    .transform
+                    .trans(position[0] + offset[0], position[1] + offset[1]),
+                graphics,
+            );
+        }
     }
 
+    /// Draws the geometry, whose polygons are filled with the next triangles of `polygon_fills`.
     fn render_geometry_2d(
         context: &Context,
         graphics: &mut G2d,
         device: &mut Device,
         draw_state: &DrawState,
         geometry_2d: &Geometry2D,
+        polygon_fills: &mut PolygonFillsIter,
         texture_buffer: &TextureBuffer,
     ) {
         match geometry_2d {
@@ -842,7 +2568,7 @@ impl PistonVisualiser {
                 line_shape,
                 transformations,
             } => {
-                for index in 0..(points.len() - 1) {
+                for index in 0..points.len().saturating_sub(1) {
                     piston_window::line::Line::new(line_color.float_array(), *line_width)
                         .shape(match line_shape {
                             gymnarium_visualisers_base::LineShape::Square => {
@@ -968,17 +2694,19 @@ impl PistonVisualiser {
                 border_width,
                 transformations,
             } => {
-                // Can draw only non-convex polygons.
                 let polygon: Vec<[f64; 2]> = points
                     .iter()
                     .map(|position| [position.x, position.y])
                     .collect();
-                piston_window::polygon::Polygon::new(fill_color.float_array()).draw(
-                    &polygon,
-                    draw_state,
-                    matrix_3x3_as_matrix_3x2(transformations.transformation_matrix()),
-                    graphics,
-                );
+                // Polygons which couldn't be triangulated have no triangles and stay unfilled.
+                for triangle in polygon_fills.next().into_iter().flatten() {
+                    piston_window::polygon::Polygon::new(fill_color.float_array()).draw(
+                        triangle,
+                        draw_state,
+                        matrix_3x3_as_matrix_3x2(transformations.transformation_matrix()),
+                        graphics,
+                    );
+                }
                 Self::draw_polygon_border(
                     &polygon,
                     border_color.float_array(),
@@ -1046,28 +2774,30 @@ impl PistonVisualiser {
                 fill_color,
                 transformations,
             } => {
-                Image::new()
-                    .rect([
-                        center_position.x - size.width / 2f64,
-                        center_position.y - size.height / 2f64,
-                        size.width,
-                        size.height,
-                    ])
-                    .maybe_color(fill_color.map(|fc| fc.float_array()))
-                    .maybe_src_rect(source_rectangle.map(|(src_pos, src_siz)| {
-                        [
-                            src_pos.x - src_siz.width / 2f64,
-                            src_pos.y - src_siz.height / 2f64,
-                            src_siz.width,
-                            src_siz.height,
-                        ]
-                    }))
-                    .draw(
-                        texture_buffer.get(texture_source).unwrap(),
-                        draw_state,
-                        matrix_3x3_as_matrix_3x2(transformations.transformation_matrix()),
-                        graphics,
-                    );
+                if let Some(texture) = texture_buffer.get(texture_source) {
+                    Image::new()
+                        .rect([
+                            center_position.x - size.width / 2f64,
+                            center_position.y - size.height / 2f64,
+                            size.width,
+                            size.height,
+                        ])
+                        .maybe_color(fill_color.map(|fc| fc.float_array()))
+                        .maybe_src_rect(source_rectangle.map(|(src_pos, src_siz)| {
+                            [
+                                src_pos.x - src_siz.width / 2f64,
+                                src_pos.y - src_siz.height / 2f64,
+                                src_siz.width,
+                                src_siz.height,
+                            ]
+                        }))
+                        .draw(
+                            texture,
+                            draw_state,
+                            matrix_3x3_as_matrix_3x2(transformations.transformation_matrix()),
+                            graphics,
+                        );
+                }
             }
             Geometry2D::Group(geometries) => {
                 for geometry in geometries {
@@ -1077,6 +2807,7 @@ impl PistonVisualiser {
                         device,
                         draw_state,
                         geometry,
+                        polygon_fills,
                         texture_buffer,
                     );
                 }
@@ -1111,7 +2842,159 @@ impl PistonVisualiser {
         }
     }
 
-    fn window_viewport() -> Viewport2D {
+    pub(crate) fn synced_data_of<
+        DrawableEnvironmentError: Error,
+        DrawableEnvironment: TwoDimensionalDrawableEnvironment<DrawableEnvironmentError>,
+    >(
+        drawable_environment: &DrawableEnvironment,
+    ) -> Result<PistonVisualiserSyncedData, DrawableEnvironmentError> {
+        let preferred_view = drawable_environment.preferred_view();
+
+        let pref_viewport = if let Some((pref_viewport, _)) = preferred_view {
+            pref_viewport
+        } else {
+            Viewport2D::with(Position2D::zero(), Size2D::with(2f64, 2f64))
+        };
+
+        let geometries_2d = drawable_environment
+            .draw_two_dimensional()?
+            .into_iter()
+            .map(|geometry| geometry.transform(&pref_viewport, &Self::window_viewport()))
+            .collect::<Arc<[Geometry2D]>>();
+
+        let background_color = drawable_environment.preferred_background_color();
+
+        Ok(PistonVisualiserSyncedData {
+            geometry_2ds: geometries_2d,
+            preferred_view,
+            background_color,
+            texts: Arc::default(),
+            metadata: Arc::default(),
+        })
+    }
+
+    pub(crate) fn extended_synced_data_of<
+        DrawableEnvironmentError: Error,
+        DrawableEnvironment: ExtendedTwoDimensionalDrawableEnvironment<DrawableEnvironmentError>,
+    >(
+        drawable_environment: &DrawableEnvironment,
+    ) -> Result<PistonVisualiserSyncedData, DrawableEnvironmentError> {
+        let mut data = Self::synced_data_of(drawable_environment)?;
+
+        let pref_viewport = if let Some((pref_viewport, _)) = data.preferred_view {
+            pref_viewport
+        } else {
+            Viewport2D::with(Position2D::zero(), Size2D::with(2f64, 2f64))
+        };
+
+        data.texts = drawable_environment
+            .draw_texts()?
+            .into_iter()
+            .map(|text| text.transform(&pref_viewport, &Self::window_viewport()))
+            .collect();
+        data.metadata = drawable_environment.draw_metadata()?.into();
+
+        Ok(data)
+    }
+
+    pub(crate) fn viewport_transformation(from: &Viewport2D, to: &Viewport2D) -> Transformation2D {
+        Transformation2D::composition(
+            "ViewportTransformation".to_string(),
+            vec![
+                Transformation2D::translation(from.center.vector_to(&Position2D::zero())),
+                Transformation2D::scale(
+                    to.size.width / from.size.width,
+                    to.size.height / from.size.height,
+                ),
+                Transformation2D::translation(Position2D::zero().vector_to(&to.center)),
+            ],
+        )
+    }
+
+    pub(crate) fn view_transformation(
+        preferred_view: &Option<(Viewport2D, Viewport2DModification)>,
+        viewport_rect: [i32; 4],
+    ) -> (Option<[u32; 4]>, Transformation2D) {
+        if let Some((viewport, viewport_mod)) = preferred_view {
+            match viewport_mod {
+                Viewport2DModification::LooseAspectRatio => (None, Transformation2D::identity()),
+                Viewport2DModification::KeepAspectRatio
+                | Viewport2DModification::KeepAspectRatioAndScissorRemains => {
+                    let (scale_x, scale_y) = Self::keep_aspect_ratio_scale(
+                        viewport,
+                        [viewport_rect[2] as f64, viewport_rect[3] as f64],
+                    );
+                    let w = scale_x * viewport_rect[2] as f64;
+                    let h = scale_y * viewport_rect[3] as f64;
+
+                    let t = Transformation2D::composition(
+                        "KeepAspectRatio".to_string(),
+                        vec![
+                            Transformation2D::translation(
+                                Self::window_viewport()
+                                    .center
+                                    .vector_to(&Position2D::zero()),
+                            ),
+                            Transformation2D::scale(scale_x, scale_y),
+                            Transformation2D::translation(
+                                Position2D::zero().vector_to(&Self::window_viewport().center),
+                            ),
+                        ],
+                    );
+
+                    let scissor = if *viewport_mod
+                        == Viewport2DModification::KeepAspectRatioAndScissorRemains
+                    {
+                        Some([
+                            (((viewport_rect[2] as f64) - w) / 2f64) as u32,
+                            (((viewport_rect[3] as f64) - h) / 2f64) as u32,
+                            w as u32,
+                            h as u32,
+                        ])
+                    } else {
+                        None
+                    };
+
+                    (scissor, t)
+                }
+            }
+        } else {
+            (None, Transformation2D::identity())
+        }
+    }
+
+    /// Factors the window viewport is scaled by to keep the aspect ratio of the viewport in a
+    /// window of the given size, i.e. the letterboxed fraction of the window on each axis.
+    pub(crate) fn keep_aspect_ratio_scale(
+        viewport: &Viewport2D,
+        window_size: [f64; 2],
+    ) -> (f64, f64) {
+        let mut h = window_size[1];
+        let mut w = viewport.size.width / viewport.size.height * h;
+        if w > window_size[0] {
+            w = window_size[0];
+            h = viewport.size.height / viewport.size.width * w;
+        }
+        (w / window_size[0], h / window_size[1])
+    }
+
+    /// Like `view_transformation`, but additionally zooms and pans by the camera.
+    pub(crate) fn camera_view_transformation(
+        preferred_view: &Option<(Viewport2D, Viewport2DModification)>,
+        viewport_rect: [i32; 4],
+        camera: Camera,
+    ) -> (Option<[u32; 4]>, Transformation2D) {
+        let (scissor, transform) = Self::view_transformation(preferred_view, viewport_rect);
+        (
+            scissor,
+            Transformation2D::composition(
+                "CameraView".to_string(),
+                vec![transform, camera.transformation()],
+            ),
+        )
+    }
+
+    pub(crate) fn window_viewport() -> Viewport2D {
         Viewport2D::with(Position2D::zero(), Size2D::with(2f64, 2f64))
     }
 }
@@ -1128,8 +3011,10 @@ impl Visualiser<PistonVisualiserError> for PistonVisualiser {
         if let Some(jh) = self.join_handle.take() {
             self.close_requested
                 .store(true, std::sync::atomic::Ordering::Relaxed);
-            jh.join().map_err(|e| {
-                PistonVisualiserError::CloseCouldNotJoinRenderThread(format!("{:?}", e))
+            jh.join().map_err(|payload| {
+                PistonVisualiserError::CloseCouldNotJoinRenderThread(panic_message(
+                    payload.as_ref(),
+                ))
             })
         } else {
             Ok(())
@@ -1137,51 +3022,61 @@ impl Visualiser<PistonVisualiserError> for PistonVisualiser {
     }
 }
 
-impl<DrawableEnvironmentError: Error>
+impl<DrawableEnvironmentError: Error + 'static>
     TwoDimensionalVisualiser<
         FurtherPistonVisualiserError<DrawableEnvironmentError>,
         PistonVisualiserError,
         DrawableEnvironmentError,
     > for PistonVisualiser
 {
+    /// Draws all geometries of the environment and hands them over to the render thread, if
+    /// they differ from the last frame. As the environment gives no hint whether anything
+    /// changed, every call costs a full comparison with the last frame;
+    /// `render_extended_two_dimensional` with `frame_generation` avoids it.
     fn render_two_dimensional<
         DrawableEnvironment: TwoDimensionalDrawableEnvironment<DrawableEnvironmentError>,
     >(
         &mut self,
         drawable_environment: &DrawableEnvironment,
     ) -> Result<(), FurtherPistonVisualiserError<DrawableEnvironmentError>> {
-        let new_preferred_view = drawable_environment.preferred_view();
+        let environment_started = Instant::now();
+        let data = Self::synced_data_of(drawable_environment)?;
+        self.last_frame_generation = None;
+        self.submit(data, environment_started.elapsed())
+    }
+}
 
-        let pref_viewport = if let Some((pref_viewport, _)) = new_preferred_view {
-            pref_viewport
-        } else {
-            Viewport2D::with(Position2D::zero(), Size2D::with(2f64, 2f64))
-        };
+/* --- --- --- ExtendedTwoDimensionalDrawableEnvironment --- --- --- */
 
-        let new_geometries_2d = drawable_environment
-            .draw_two_dimensional()?
-            .into_iter()
-            .map(|geometry| geometry.transform(&pref_viewport, &Self::window_viewport()))
-            .collect::<Vec<Geometry2D>>();
+/// Key/value pairs describing a geometry, e.g. `("reward", "1.5")`.
+pub type GeometryMetadata = Vec<(String, String)>;
 
-        let new_background_color = drawable_environment.preferred_background_color();
+/// Environment which can provide more than geometries to the visualisers of this crate.
+///
+/// All methods have default implementations, so only the needed ones have to be implemented.
+/// Use `render_extended_two_dimensional` of the visualisers to render them.
+pub trait ExtendedTwoDimensionalDrawableEnvironment<DrawableEnvironmentError: Error>:
+    TwoDimensionalDrawableEnvironment<DrawableEnvironmentError>
+{
+    /// Texts given in the same coordinates as the geometries, drawn on top of them.
+    fn draw_texts(&self) -> Result<Vec<Text2D>, DrawableEnvironmentError> {
+        Ok(Vec::new())
+    }
 
-        if new_geometries_2d != self.last_geometries_2d
-            || new_preferred_view != self.last_preferred_view
-            || new_background_color != self.last_preferred_background_color
-        {
-            let mut locked_latest_data = self.latest_data.lock().map_err(|e| {
-                FurtherPistonVisualiserError::LockingFailedInternally(format!("{}", e))
-            })?;
-            (*locked_latest_data) = Some((
-                new_geometries_2d.clone(),
-                new_preferred_view,
-                new_background_color,
-            ));
-            self.last_geometries_2d = new_geometries_2d;
-            self.last_preferred_view = new_preferred_view;
-            self.last_preferred_background_color = new_background_color;
-        }
-        Ok(())
+    /// Key/value pairs for the geometries with the same index in `draw_two_dimensional`, e.g. the
+    /// id or reward of an agent, shown as tooltip when hovering the geometry if enabled with
+    /// `PistonVisualiserSettings::tooltips`. Groups share the metadata of their index.
+    fn draw_metadata(&self) -> Result<Vec<GeometryMetadata>, DrawableEnvironmentError> {
+        Ok(Vec::new())
+    }
+
+    /// Number which only changes when the drawn geometries, texts or metadata change, e.g. a counter
+    /// increased on every step of the environment.
+    ///
+    /// As long as it and the preferred view stay the same,
+    /// `PistonVisualiser::render_extended_two_dimensional` reuses the last frame instead of
+    /// drawing and comparing the geometries again. The background color is still queried anew.
+    fn frame_generation(&self) -> Option<u64> {
+        None
     }
 }
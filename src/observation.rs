@@ -0,0 +1,294 @@
+//! Rendering environments straight into flat pixel buffers, which can be fed to agents as
+//! observations.
+//!
+//! The geometries are rasterised by the `HeadlessVisualiser`, so the result is deterministic
+//! across runs and machines and doesn't need a display or a GPU.
+
+use std::collections::VecDeque;
+use std::error::Error;
+
+use image::RgbaImage;
+
+use gymnarium_visualisers_base::{TwoDimensionalDrawableEnvironment, TwoDimensionalVisualiser};
+
+use crate::{FurtherPistonVisualiserError, HeadlessVisualiser};
+
+/* --- --- --- ColorFormat --- --- --- */
+
+/// Layout of the channels of a single pixel inside an observation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColorFormat {
+    /// One luma channel (ITU-R BT.601 weights).
+    Grayscale,
+    Rgb,
+    Rgba,
+}
+
+impl ColorFormat {
+    pub fn channels(&self) -> usize {
+        match self {
+            Self::Grayscale => 1,
+            Self::Rgb => 3,
+            Self::Rgba => 4,
+        }
+    }
+
+    fn write_pixel(&self, pixel: [u8; 4], buffer: &mut Vec<u8>) {
+        // Without alpha channel the pixel is composed onto black.
+        let opaque = |channel: u8| ((channel as u32 * pixel[3] as u32 + 127) / 255) as u8;
+        match self {
+            Self::Grayscale => {
+                let (r, g, b) = (
+                    opaque(pixel[0]) as u32,
+                    opaque(pixel[1]) as u32,
+                    opaque(pixel[2]) as u32,
+                );
+                buffer.push(((299 * r + 587 * g + 114 * b + 500) / 1000) as u8);
+            }
+            Self::Rgb => {
+                buffer.push(opaque(pixel[0]));
+                buffer.push(opaque(pixel[1]));
+                buffer.push(opaque(pixel[2]));
+            }
+            Self::Rgba => buffer.extend_from_slice(&pixel),
+        }
+    }
+
+    fn convert(&self, frame: &RgbaImage) -> Vec<u8> {
+        let mut buffer =
+            Vec::with_capacity(frame.width() as usize * frame.height() as usize * self.channels());
+        frame
+            .pixels()
+            .for_each(|pixel| self.write_pixel(pixel.0, &mut buffer));
+        buffer
+    }
+}
+
+/* --- --- --- ObservationComponent --- --- --- */
+
+/// Type of a single value inside an observation buffer.
+pub trait ObservationComponent: Copy {
+    fn from_u8(value: u8) -> Self;
+}
+
+impl ObservationComponent for u8 {
+    fn from_u8(value: u8) -> Self {
+        value
+    }
+}
+
+/// Values are scaled into `0.0..=1.0`.
+impl ObservationComponent for f32 {
+    fn from_u8(value: u8) -> Self {
+        value as f32 / 255f32
+    }
+}
+
+/* --- --- --- ObservationRenderer --- --- --- */
+
+/// Renders environments into flat observation buffers of a fixed size and color format.
+///
+/// The buffer is laid out row by row from the top left pixel and holds `channels()` values per
+/// pixel. With frame stacking the buffers of the last `frame_stack_size` frames are concatenated,
+/// oldest first. Until enough frames were rendered the oldest frame is repeated.
+pub struct ObservationRenderer {
+    visualiser: HeadlessVisualiser,
+    color_format: ColorFormat,
+    frame_stack_size: usize,
+    frame_stack: VecDeque<Vec<u8>>,
+}
+
+impl ObservationRenderer {
+    pub fn new(width: u32, height: u32, color_format: ColorFormat) -> Self {
+        Self {
+            visualiser: HeadlessVisualiser::new(width, height),
+            color_format,
+            frame_stack_size: 1,
+            frame_stack: VecDeque::new(),
+        }
+    }
+
+    /// Stacks the last `frame_stack_size` frames into each observation.
+    pub fn with_frame_stack(mut self, frame_stack_size: usize) -> Self {
+        self.frame_stack_size = frame_stack_size.max(1);
+        self
+    }
+
+    pub fn color_format(&self) -> ColorFormat {
+        self.color_format
+    }
+
+    /// Length of the buffers returned by `render`.
+    pub fn observation_len(&self) -> usize {
+        let frame = self.visualiser.frame();
+        frame.width() as usize
+            * frame.height() as usize
+            * self.color_format.channels()
+            * self.frame_stack_size
+    }
+
+    /// Forgets all stacked frames, e.g. at the start of a new episode.
+    pub fn reset(&mut self) {
+        self.frame_stack.clear();
+    }
+
+    pub fn render<
+        Component: ObservationComponent,
+        DrawableEnvironmentError: Error + 'static,
+        DrawableEnvironment: TwoDimensionalDrawableEnvironment<DrawableEnvironmentError>,
+    >(
+        &mut self,
+        drawable_environment: &DrawableEnvironment,
+    ) -> Result<Vec<Component>, FurtherPistonVisualiserError<DrawableEnvironmentError>> {
+        self.visualiser
+            .render_two_dimensional(drawable_environment)?;
+        Ok(self.observe())
+    }
+
+    /// Stacks the frame the visualiser rendered last and returns the resulting observation.
+    fn observe<Component: ObservationComponent>(&mut self) -> Vec<Component> {
+        let frame = self.color_format.convert(self.visualiser.frame());
+
+        while self.frame_stack.len() >= self.frame_stack_size {
+            let _ = self.frame_stack.pop_front();
+        }
+        self.frame_stack.push_back(frame);
+
+        let mut observation = Vec::with_capacity(self.observation_len());
+        let oldest = &self.frame_stack[0];
+        for _ in self.frame_stack.len()..self.frame_stack_size {
+            observation.extend(oldest.iter().map(|value| Component::from_u8(*value)));
+        }
+        for stacked_frame in &self.frame_stack {
+            observation.extend(stacked_frame.iter().map(|value| Component::from_u8(*value)));
+        }
+        observation
+    }
+}
+
+/// Renders the environment once into an observation buffer of the given size and color format.
+///
+/// To stack frames or to render repeatedly without reloading textures use an
+/// `ObservationRenderer`.
+pub fn render_to_observation<
+    Component: ObservationComponent,
+    DrawableEnvironmentError: Error + 'static,
+    DrawableEnvironment: TwoDimensionalDrawableEnvironment<DrawableEnvironmentError>,
+>(
+    drawable_environment: &DrawableEnvironment,
+    width: u32,
+    height: u32,
+    color_format: ColorFormat,
+) -> Result<Vec<Component>, FurtherPistonVisualiserError<DrawableEnvironmentError>> {
+    ObservationRenderer::new(width, height, color_format).render(drawable_environment)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use image::Rgba;
+
+    use gymnarium_base::math::{Position2D, Size2D, Transformation2D};
+    use gymnarium_visualisers_base::{Color, CornerShape, Geometry2D};
+
+    use crate::PistonVisualiserSyncedData;
+
+    fn rectangle(center: [f64; 2], size: [f64; 2]) -> Geometry2D {
+        Geometry2D::Rectangle {
+            center_position: Position2D::with(center[0], center[1]),
+            size: Size2D::with(size[0], size[1]),
+            fill_color: Color::black(),
+            border_color: Color::black(),
+            border_width: 0f64,
+            corner_shape: CornerShape::Square,
+            transformations: Transformation2D::identity(),
+        }
+    }
+
+    fn observe(renderer: &mut ObservationRenderer, geometry_2ds: Vec<Geometry2D>) -> Vec<u8> {
+        renderer
+            .visualiser
+            .render_frame(&PistonVisualiserSyncedData {
+                geometry_2ds: geometry_2ds.into(),
+                ..Default::default()
+            });
+        renderer.observe()
+    }
+
+    #[test]
+    fn converts_pixels_into_every_color_format() {
+        let mut frame = RgbaImage::new(2, 1);
+        frame.put_pixel(0, 0, Rgba([255, 0, 0, 255]));
+        frame.put_pixel(1, 0, Rgba([255, 255, 255, 128]));
+
+        assert_eq!(ColorFormat::Grayscale.convert(&frame), vec![76, 128]);
+        assert_eq!(
+            ColorFormat::Rgb.convert(&frame),
+            vec![255, 0, 0, 128, 128, 128]
+        );
+        assert_eq!(
+            ColorFormat::Rgba.convert(&frame),
+            vec![255, 0, 0, 255, 255, 255, 255, 128]
+        );
+    }
+
+    #[test]
+    fn scales_float_components_into_the_unit_interval() {
+        assert_eq!(f32::from_u8(0), 0f32);
+        assert_eq!(f32::from_u8(255), 1f32);
+        assert_eq!(u8::from_u8(128), 128);
+    }
+
+    #[test]
+    fn stacks_frames_oldest_first_and_repeats_the_oldest_frame() {
+        let mut renderer = ObservationRenderer::new(2, 1, ColorFormat::Rgba).with_frame_stack(3);
+        let left = vec![rectangle([-0.5f64, 0f64], [1f64, 2f64])];
+        let right = vec![rectangle([0.5f64, 0f64], [1f64, 2f64])];
+        let alphas = |observation: Vec<u8>| {
+            assert_eq!(observation.len(), 2 * 4 * 3);
+            observation
+                .chunks_exact(4)
+                .map(|pixel| pixel[3])
+                .collect::<Vec<u8>>()
+        };
+
+        assert_eq!(
+            alphas(observe(&mut renderer, left.clone())),
+            vec![255, 0, 255, 0, 255, 0]
+        );
+        assert_eq!(
+            alphas(observe(&mut renderer, right)),
+            vec![255, 0, 255, 0, 0, 255]
+        );
+        assert_eq!(
+            alphas(observe(&mut renderer, left)),
+            vec![255, 0, 0, 255, 255, 0]
+        );
+
+        renderer.reset();
+        assert_eq!(
+            alphas(observe(&mut renderer, Vec::new())),
+            vec![0, 0, 0, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn renders_the_same_observation_every_time() {
+        let geometry_2ds = vec![
+            rectangle([-0.3f64, 0.2f64], [0.7f64, 0.9f64]),
+            rectangle([0.4f64, -0.5f64], [0.3f64, 0.3f64]),
+        ];
+        let first = observe(
+            &mut ObservationRenderer::new(16, 12, ColorFormat::Rgba),
+            geometry_2ds.clone(),
+        );
+        let second = observe(
+            &mut ObservationRenderer::new(16, 12, ColorFormat::Rgba),
+            geometry_2ds,
+        );
+        assert_eq!(first.len(), 16 * 12 * 4);
+        assert!(first.chunks_exact(4).any(|pixel| pixel[3] == 255));
+        assert_eq!(first, second);
+    }
+}
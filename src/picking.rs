@@ -0,0 +1,522 @@
+//! Hit-testing of geometries, to find out which geometry lies at a position, e.g. under the
+//! cursor.
+
+use gymnarium_base::math::Position2D;
+use gymnarium_visualisers_base::input::MouseButton;
+use gymnarium_visualisers_base::Geometry2D;
+
+use crate::GeometryHandle;
+
+type Matrix = [[f64; 3]; 3];
+
+/// Distance in pixels within which the cursor still hits points, lines and borders.
+pub(crate) const PICK_TOLERANCE_PIXELS: f64 = 4f64;
+
+const IDENTITY: Matrix = [[1f64, 0f64, 0f64], [0f64, 1f64, 0f64], [0f64, 0f64, 1f64]];
+
+/* --- --- --- PickedGeometry --- --- --- */
+
+/// Geometry found under the cursor of a window.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PickedGeometry {
+    /// Geometry of the last `render_two_dimensional` with its index path, like returned by
+    /// `pick_geometry`.
+    Rendered(Vec<usize>),
+    /// Geometry of the retained scene, with the index path inside it if it's a group.
+    Scene {
+        handle: GeometryHandle,
+        path: Vec<usize>,
+    },
+}
+
+/// Event pushed into the `PistonVisualiserInputProvider` if enabled with
+/// `PistonVisualiserSettings::pick_events`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PickEvent {
+    /// The top-most geometry under the cursor changed, `None` if it left all geometries.
+    Hovered(Option<PickedGeometry>),
+    /// A mouse button was pressed on the given geometry, or on none.
+    Clicked {
+        button: MouseButton,
+        geometry: Option<PickedGeometry>,
+    },
+}
+
+/* --- --- --- pick_geometry --- --- --- */
+
+/// Finds the top-most geometry at the position, i.e. the last drawn one, and returns its index
+/// path: the index in `geometry_2ds` followed by the indices inside the groups containing it.
+///
+/// The position and tolerance are given in the coordinates of the geometries, before their
+/// transformations. Points and lines are hit within the tolerance, filled shapes and images
+/// when they contain the position or their border is within the tolerance.
+pub fn pick_geometry(
+    geometry_2ds: &[Geometry2D],
+    position: Position2D,
+    tolerance: f64,
+) -> Option<Vec<usize>> {
+    pick_with(
+        geometry_2ds,
+        [position.x, position.y],
+        tolerance,
+        &IDENTITY,
+        &IDENTITY,
+    )
+}
+
+/// Like `pick_geometry`, but every geometry is additionally transformed by `outer` after its own
+/// transformations, e.g. into pixels of the window. Points are transformed by `point_outer`
+/// instead, see `point_pixel_matrix`.
+pub(crate) fn pick_with(
+    geometry_2ds: &[Geometry2D],
+    position: [f64; 2],
+    tolerance: f64,
+    outer: &Matrix,
+    point_outer: &Matrix,
+) -> Option<Vec<usize>> {
+    geometry_2ds
+        .iter()
+        .enumerate()
+        .rev()
+        .find_map(|(index, geometry)| {
+            if let Geometry2D::Group(geometries) = geometry {
+                pick_with(geometries, position, tolerance, outer, point_outer).map(|mut path| {
+                    path.insert(0, index);
+                    path
+                })
+            } else if hits(geometry, position, tolerance, outer, point_outer) {
+                Some(vec![index])
+            } else {
+                None
+            }
+        })
+}
+
+/// Matrix transforming normalized window coordinates into pixels of a window with the given
+/// size, like the viewport of piston does.
+pub(crate) fn window_pixel_matrix(window_size: [f64; 2]) -> Matrix {
+    [
+        [window_size[0] / 2f64, 0f64, window_size[0] / 2f64],
+        [0f64, -window_size[1] / 2f64, window_size[1] / 2f64],
+        [0f64, 0f64, 1f64],
+    ]
+}
+
+/// Matrix transforming normalized window coordinates of points into pixels of a window with the
+/// given draw size. Unlike the other geometries, `render_geometry_2d` draws points as a pixel
+/// wide ellipse starting at `(x + 1) / 2 * draw_size` on both axes, without flipping `y`.
+pub(crate) fn point_pixel_matrix(draw_size: [f64; 2]) -> Matrix {
+    [
+        [draw_size[0] / 2f64, 0f64, draw_size[0] / 2f64 + 0.5f64],
+        [0f64, draw_size[1] / 2f64, draw_size[1] / 2f64 + 0.5f64],
+        [0f64, 0f64, 1f64],
+    ]
+}
+
+fn hits(
+    geometry: &Geometry2D,
+    position: [f64; 2],
+    tolerance: f64,
+    outer: &Matrix,
+    point_outer: &Matrix,
+) -> bool {
+    let transformations = match geometry {
+        Geometry2D::Point {
+            position: point,
+            transformations,
+            ..
+        } => {
+            // Points keep their size under any transformation, so they're hit-tested where
+            // they are drawn.
+            let matrix = multiply(point_outer, &transformations.transformation_matrix());
+            return distance(position, apply(&matrix, at(point))) <= tolerance;
+        }
+        Geometry2D::Line {
+            transformations, ..
+        }
+        | Geometry2D::Polyline {
+            transformations, ..
+        }
+        | Geometry2D::Triangle {
+            transformations, ..
+        }
+        | Geometry2D::Square {
+            transformations, ..
+        }
+        | Geometry2D::Rectangle {
+            transformations, ..
+        }
+        | Geometry2D::Polygon {
+            transformations, ..
+        }
+        | Geometry2D::Circle {
+            transformations, ..
+        }
+        | Geometry2D::Ellipse {
+            transformations, ..
+        }
+        | Geometry2D::Image {
+            transformations, ..
+        } => transformations,
+        Geometry2D::Group(_) => return false,
+    };
+    let matrix = multiply(outer, &transformations.transformation_matrix());
+    let determinant = matrix[0][0] * matrix[1][1] - matrix[0][1] * matrix[1][0];
+    if determinant.abs() <= f64::EPSILON {
+        return false;
+    }
+    // Hit-testing happens in the coordinates of the geometry, where the tolerance is scaled by
+    // the mean scale of the transformation.
+    let p = inverse_apply(&matrix, determinant, position);
+    let tolerance = tolerance / determinant.abs().sqrt();
+    match geometry {
+        Geometry2D::Point { .. } => false,
+        Geometry2D::Line {
+            points, line_width, ..
+        } => {
+            // Piston draws lines with `line_width` as radius.
+            distance_to_segment(p, at(&points[0]), at(&points[1])) <= line_width + tolerance
+        }
+        Geometry2D::Polyline {
+            points, line_width, ..
+        } => points.windows(2).any(|segment| {
+            distance_to_segment(p, at(&segment[0]), at(&segment[1])) <= line_width + tolerance
+        }),
+        Geometry2D::Triangle {
+            points,
+            border_width,
+            ..
+        } => polygon_hit(p, points, border_width + tolerance),
+        Geometry2D::Polygon {
+            points,
+            border_width,
+            ..
+        } => polygon_hit(p, points, border_width + tolerance),
+        Geometry2D::Square {
+            center_position,
+            edge_length,
+            border_width,
+            ..
+        } => rectangle_hit(
+            p,
+            at(center_position),
+            [*edge_length, *edge_length],
+            border_width + tolerance,
+        ),
+        Geometry2D::Rectangle {
+            center_position,
+            size,
+            border_width,
+            ..
+        } => rectangle_hit(
+            p,
+            at(center_position),
+            [size.width, size.height],
+            border_width + tolerance,
+        ),
+        Geometry2D::Image {
+            center_position,
+            size,
+            ..
+        } => rectangle_hit(p, at(center_position), [size.width, size.height], tolerance),
+        Geometry2D::Circle {
+            center_position,
+            radius,
+            border_width,
+            ..
+        } => distance(p, at(center_position)) <= radius + border_width + tolerance,
+        Geometry2D::Ellipse {
+            center_position,
+            size,
+            border_width,
+            ..
+        } => {
+            // Same rectangle the ellipse is drawn into by `render_geometry_2d`.
+            let center = [
+                center_position.x - size.width / 2f64,
+                center_position.y - size.height / 2f64,
+            ];
+            let radius_x = size.width / 2f64 + border_width + tolerance;
+            let radius_y = size.height / 2f64 + border_width + tolerance;
+            radius_x > 0f64
+                && radius_y > 0f64
+                && ((p[0] - center[0]) / radius_x).powi(2) + ((p[1] - center[1]) / radius_y).powi(2)
+                    <= 1f64
+        }
+        Geometry2D::Group(_) => false,
+    }
+}
+
+fn at(position: &Position2D) -> [f64; 2] {
+    [position.x, position.y]
+}
+
+fn multiply(a: &Matrix, b: &Matrix) -> Matrix {
+    let mut product = [[0f64; 3]; 3];
+    for (row, product_row) in product.iter_mut().enumerate() {
+        for (column, value) in product_row.iter_mut().enumerate() {
+            *value = (0..3).map(|k| a[row][k] * b[k][column]).sum();
+        }
+    }
+    product
+}
+
+fn apply(matrix: &Matrix, position: [f64; 2]) -> [f64; 2] {
+    [
+        matrix[0][0] * position[0] + matrix[0][1] * position[1] + matrix[0][2],
+        matrix[1][0] * position[0] + matrix[1][1] * position[1] + matrix[1][2],
+    ]
+}
+
+/// Applies the inverse of the affine `matrix` with the given determinant to the position.
+fn inverse_apply(matrix: &Matrix, determinant: f64, position: [f64; 2]) -> [f64; 2] {
+    let x = position[0] - matrix[0][2];
+    let y = position[1] - matrix[1][2];
+    [
+        (matrix[1][1] * x - matrix[0][1] * y) / determinant,
+        (matrix[0][0] * y - matrix[1][0] * x) / determinant,
+    ]
+}
+
+fn distance(a: [f64; 2], b: [f64; 2]) -> f64 {
+    (a[0] - b[0]).hypot(a[1] - b[1])
+}
+
+fn distance_to_segment(p: [f64; 2], a: [f64; 2], b: [f64; 2]) -> f64 {
+    let ab = [b[0] - a[0], b[1] - a[1]];
+    let length_squared = ab[0] * ab[0] + ab[1] * ab[1];
+    if length_squared == 0f64 {
+        return distance(p, a);
+    }
+    let t = (((p[0] - a[0]) * ab[0] + (p[1] - a[1]) * ab[1]) / length_squared).clamp(0f64, 1f64);
+    distance(p, [a[0] + t * ab[0], a[1] + t * ab[1]])
+}
+
+/// Whether the polygon contains the position (even-odd rule) or its outline is within `margin`.
+fn polygon_hit(p: [f64; 2], points: &[Position2D], margin: f64) -> bool {
+    let mut inside = false;
+    for index in 0..points.len() {
+        let a = at(&points[index]);
+        let b = at(&points[(index + 1) % points.len()]);
+        if distance_to_segment(p, a, b) <= margin {
+            return true;
+        }
+        if (a[1] > p[1]) != (b[1] > p[1])
+            && p[0] < (b[0] - a[0]) * (p[1] - a[1]) / (b[1] - a[1]) + a[0]
+        {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+fn rectangle_hit(p: [f64; 2], center: [f64; 2], size: [f64; 2], margin: f64) -> bool {
+    (p[0] - center[0]).abs() <= size[0].abs() / 2f64 + margin
+        && (p[1] - center[1]).abs() <= size[1].abs() / 2f64 + margin
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use gymnarium_base::math::{Size2D, Transformation2D};
+    use gymnarium_visualisers_base::{Color, CornerShape, LineShape};
+
+    fn line(from: [f64; 2], to: [f64; 2], line_width: f64) -> Geometry2D {
+        Geometry2D::Line {
+            points: [
+                Position2D::with(from[0], from[1]),
+                Position2D::with(to[0], to[1]),
+            ],
+            line_color: Color::black(),
+            line_width,
+            line_shape: LineShape::Square,
+            transformations: Transformation2D::identity(),
+        }
+    }
+
+    fn rectangle(
+        center: [f64; 2],
+        size: [f64; 2],
+        transformations: Transformation2D,
+    ) -> Geometry2D {
+        Geometry2D::Rectangle {
+            center_position: Position2D::with(center[0], center[1]),
+            size: Size2D::with(size[0], size[1]),
+            fill_color: Color::black(),
+            border_color: Color::black(),
+            border_width: 0f64,
+            corner_shape: CornerShape::Square,
+            transformations,
+        }
+    }
+
+    fn polygon(points: &[[f64; 2]]) -> Geometry2D {
+        Geometry2D::Polygon {
+            points: points
+                .iter()
+                .map(|point| Position2D::with(point[0], point[1]))
+                .collect(),
+            fill_color: Color::black(),
+            border_color: Color::black(),
+            border_width: 0f64,
+            transformations: Transformation2D::identity(),
+        }
+    }
+
+    #[test]
+    fn finds_nothing_outside_of_all_geometries() {
+        let geometries = vec![rectangle(
+            [0f64, 0f64],
+            [2f64, 2f64],
+            Transformation2D::identity(),
+        )];
+        assert_eq!(
+            pick_geometry(&geometries, Position2D::with(3f64, 0f64), 0.5f64),
+            None
+        );
+    }
+
+    #[test]
+    fn prefers_the_top_most_geometry() {
+        let geometries = vec![
+            rectangle([0f64, 0f64], [4f64, 4f64], Transformation2D::identity()),
+            rectangle([1f64, 1f64], [2f64, 2f64], Transformation2D::identity()),
+        ];
+        assert_eq!(
+            pick_geometry(&geometries, Position2D::with(1f64, 1f64), 0f64),
+            Some(vec![1])
+        );
+        assert_eq!(
+            pick_geometry(&geometries, Position2D::with(-1f64, -1f64), 0f64),
+            Some(vec![0])
+        );
+    }
+
+    #[test]
+    fn returns_index_path_into_groups() {
+        let geometries = vec![
+            rectangle([5f64, 5f64], [1f64, 1f64], Transformation2D::identity()),
+            Geometry2D::Group(vec![
+                rectangle([5f64, 5f64], [1f64, 1f64], Transformation2D::identity()),
+                Geometry2D::Group(vec![rectangle(
+                    [0f64, 0f64],
+                    [1f64, 1f64],
+                    Transformation2D::identity(),
+                )]),
+            ]),
+        ];
+        assert_eq!(
+            pick_geometry(&geometries, Position2D::with(0f64, 0f64), 0f64),
+            Some(vec![1, 1, 0])
+        );
+    }
+
+    #[test]
+    fn hits_lines_within_their_drawn_radius() {
+        let geometries = vec![line([0f64, 0f64], [10f64, 0f64], 2f64)];
+        assert_eq!(
+            pick_geometry(&geometries, Position2D::with(5f64, 1.9f64), 0f64),
+            Some(vec![0])
+        );
+        assert_eq!(
+            pick_geometry(&geometries, Position2D::with(5f64, 2.9f64), 1f64),
+            Some(vec![0])
+        );
+        assert_eq!(
+            pick_geometry(&geometries, Position2D::with(5f64, 3.1f64), 1f64),
+            None
+        );
+    }
+
+    #[test]
+    fn hits_concave_polygons_only_inside() {
+        // A U shape open to the top, whose notch lies around (2, 3).
+        let geometries = vec![polygon(&[
+            [0f64, 0f64],
+            [4f64, 0f64],
+            [4f64, 4f64],
+            [3f64, 4f64],
+            [3f64, 1f64],
+            [1f64, 1f64],
+            [1f64, 4f64],
+            [0f64, 4f64],
+        ])];
+        assert_eq!(
+            pick_geometry(&geometries, Position2D::with(0.5f64, 3f64), 0f64),
+            Some(vec![0])
+        );
+        assert_eq!(
+            pick_geometry(&geometries, Position2D::with(2f64, 3f64), 0.1f64),
+            None
+        );
+    }
+
+    #[test]
+    fn applies_the_transformations_of_the_geometry() {
+        let geometries = vec![rectangle(
+            [0f64, 0f64],
+            [1f64, 1f64],
+            Transformation2D::composition(
+                "Test".to_string(),
+                vec![
+                    Transformation2D::scale(4f64, 4f64),
+                    Transformation2D::translation(
+                        Position2D::zero().vector_to(&Position2D::with(10f64, 0f64)),
+                    ),
+                ],
+            ),
+        )];
+        assert_eq!(
+            pick_geometry(&geometries, Position2D::with(11.5f64, 1.5f64), 0f64),
+            Some(vec![0])
+        );
+        assert_eq!(
+            pick_geometry(&geometries, Position2D::with(0f64, 0f64), 0f64),
+            None
+        );
+    }
+
+    #[test]
+    fn maps_window_pixels_like_the_viewport() {
+        let matrix = window_pixel_matrix([200f64, 100f64]);
+        let geometries = vec![rectangle(
+            [0.5f64, 0.5f64],
+            [0.2f64, 0.2f64],
+            Transformation2D::identity(),
+        )];
+        // (0.5, 0.5) lies in the upper right quarter of the window.
+        assert_eq!(
+            pick_with(&geometries, [150f64, 25f64], 0f64, &matrix, &matrix),
+            Some(vec![0])
+        );
+        assert_eq!(
+            pick_with(&geometries, [150f64, 75f64], 0f64, &matrix, &matrix),
+            None
+        );
+    }
+
+    #[test]
+    fn hits_points_where_they_are_drawn() {
+        let outer = window_pixel_matrix([200f64, 100f64]);
+        let point_outer = point_pixel_matrix([200f64, 100f64]);
+        let geometries = vec![Geometry2D::Point {
+            position: Position2D::with(0.5f64, 0.5f64),
+            color: Color::black(),
+            transformations: Transformation2D::identity(),
+        }];
+        // Drawn as the pixel starting at (150, 75), so y isn't flipped like for other geometries.
+        assert_eq!(
+            pick_with(&geometries, [150.5f64, 75.5f64], 1f64, &outer, &point_outer),
+            Some(vec![0])
+        );
+        assert_eq!(
+            pick_with(&geometries, [150.5f64, 25.5f64], 1f64, &outer, &point_outer),
+            None
+        );
+        assert_eq!(
+            pick_geometry(&geometries, Position2D::with(0.5f64, 0.5f64), 0f64),
+            Some(vec![0])
+        );
+    }
+}
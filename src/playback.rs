@@ -0,0 +1,138 @@
+//! Playback controls, with which the user pauses, single-steps and slows down the simulation
+//! from the window of a `PistonVisualiser`.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+
+use piston_window::Key;
+
+use crate::alive_wait::wait_while_alive;
+
+const MIN_SPEED: f64 = 1f64 / 16f64;
+const MAX_SPEED: f64 = 16f64;
+
+/* --- --- --- Playback --- --- --- */
+
+struct PlaybackState {
+    paused: bool,
+    /// Steps allowed to pass `wait_while_paused` although paused.
+    pending_steps: u64,
+    speed: f64,
+}
+
+/// Playback state shared between the render thread, which changes it by hotkeys, and the
+/// `PistonVisualiser`.
+#[derive(Clone)]
+pub(crate) struct Playback {
+    state: Arc<(Mutex<PlaybackState>, Condvar)>,
+    /// Counts the changes of `status`, so the render thread only locks the state after one.
+    generation: Arc<AtomicU64>,
+}
+
+impl Default for Playback {
+    fn default() -> Self {
+        Self {
+            state: Arc::new((
+                Mutex::new(PlaybackState {
+                    paused: false,
+                    pending_steps: 0,
+                    speed: 1f64,
+                }),
+                Condvar::new(),
+            )),
+            generation: Arc::new(AtomicU64::new(0)),
+        }
+    }
+}
+
+impl Playback {
+    fn lock(&self) -> MutexGuard<'_, PlaybackState> {
+        self.state.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Space toggles the pause, `.` pauses or, while paused, lets a single step pass and `+` and
+    /// `-` double and halve the requested speed.
+    pub fn handle_key(&self, key: Key) {
+        let mut state = self.lock();
+        match key {
+            Key::Space => {
+                state.paused = !state.paused;
+                state.pending_steps = 0;
+            }
+            Key::Period => {
+                if state.paused {
+                    state.pending_steps += 1;
+                }
+                state.paused = true;
+            }
+            Key::Plus | Key::Equals | Key::NumPadPlus => {
+                state.speed = (state.speed * 2f64).min(MAX_SPEED);
+            }
+            Key::Minus | Key::NumPadMinus => {
+                state.speed = (state.speed / 2f64).max(MIN_SPEED);
+            }
+            _ => return,
+        }
+        self.changed();
+    }
+
+    fn changed(&self) {
+        let _ = self.generation.fetch_add(1, Ordering::Release);
+        self.state.1.notify_all();
+    }
+
+    /// Changes whenever `status` might have changed.
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Acquire)
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.lock().paused
+    }
+
+    pub fn set_paused(&self, paused: bool) {
+        let mut state = self.lock();
+        state.paused = paused;
+        state.pending_steps = 0;
+        self.changed();
+    }
+
+    pub fn speed(&self) -> f64 {
+        self.lock().speed
+    }
+
+    pub fn set_speed(&self, speed: f64) {
+        self.lock().speed = speed.clamp(MIN_SPEED, MAX_SPEED);
+        self.changed();
+    }
+
+    /// Line shown in the window while the playback differs from running at normal speed.
+    pub fn status(&self) -> Option<String> {
+        let state = self.lock();
+        if state.paused {
+            Some("paused (space: resume, .: step)".to_string())
+        } else if state.speed != 1f64 {
+            Some(format!("speed x{}", state.speed))
+        } else {
+            None
+        }
+    }
+
+    /// Blocks while paused, unless a single step is pending, which is consumed. Waiting stops
+    /// early as soon as `is_alive` returns `false`.
+    pub fn wait_while_paused<F: Fn() -> bool>(&self, is_alive: F) {
+        let _ = wait_while_alive(
+            &self.state.1,
+            self.lock(),
+            None,
+            |state| {
+                if state.paused && state.pending_steps > 0 {
+                    state.pending_steps -= 1;
+                    return false;
+                }
+                state.paused
+            },
+            is_alive,
+        );
+    }
+}
@@ -0,0 +1,167 @@
+//! Maps positions of mouse and touch inputs from the window back into the coordinates of the
+//! environment, by inverting the transformations applied in `render`.
+
+use gymnarium_base::math::Position2D;
+use gymnarium_visualisers_base::{Viewport2D, Viewport2DModification};
+
+use crate::camera::normalized_window_position;
+use crate::{Camera, PistonVisualiser};
+
+/* --- --- --- EnvironmentPosition --- --- --- */
+
+/// Position of a mouse or touch input in the coordinates of the environment.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EnvironmentPosition {
+    /// Position in the coordinates of the preferred view of the environment, or of the view
+    /// from `-1` to `1` on both axes if it doesn't prefer one.
+    pub position: Position2D,
+    /// Whether the position lies inside the area the environment is drawn in. This excludes
+    /// the letterboxed borders of the `KeepAspectRatio` modifications, which are cut off by the
+    /// scissor of `KeepAspectRatioAndScissorRemains`.
+    pub inside_view: bool,
+}
+
+/// Maps a position in pixels of a window with the given size into the coordinates of the
+/// environment, as drawn with the preferred view and camera.
+pub(crate) fn environment_position(
+    window_position: [f64; 2],
+    window_size: [f64; 2],
+    preferred_view: &Option<(Viewport2D, Viewport2DModification)>,
+    camera: &Camera,
+) -> EnvironmentPosition {
+    let normalized = normalized_window_position(window_position, window_size);
+    let inside_window = normalized.0.abs() <= 1f64 && normalized.1.abs() <= 1f64;
+    let (x, y) = camera.unproject(normalized);
+    let (pref_viewport, view_scale) = match preferred_view {
+        Some((viewport, Viewport2DModification::LooseAspectRatio)) => (*viewport, (1f64, 1f64)),
+        Some((viewport, _)) => (
+            *viewport,
+            PistonVisualiser::keep_aspect_ratio_scale(viewport, window_size),
+        ),
+        None => (PistonVisualiser::window_viewport(), (1f64, 1f64)),
+    };
+    // The scissor stays in place when the camera moves, so it's checked before unprojecting.
+    let inside_view =
+        inside_window && normalized.0.abs() <= view_scale.0 && normalized.1.abs() <= view_scale.1;
+    let window_viewport = PistonVisualiser::window_viewport();
+    EnvironmentPosition {
+        position: Position2D::with(
+            pref_viewport.center.x
+                + (x / view_scale.0 - window_viewport.center.x) * pref_viewport.size.width
+                    / window_viewport.size.width,
+            pref_viewport.center.y
+                + (y / view_scale.1 - window_viewport.center.y) * pref_viewport.size.height
+                    / window_viewport.size.height,
+        ),
+        inside_view,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use gymnarium_base::math::{Size2D, Transformation2D};
+
+    const WINDOW_SIZE: [f64; 2] = [400f64, 400f64];
+
+    fn wide_view(
+        viewport_modification: Viewport2DModification,
+    ) -> Option<(Viewport2D, Viewport2DModification)> {
+        Some((
+            Viewport2D::with(Position2D::with(3f64, -2f64), Size2D::with(8f64, 4f64)),
+            viewport_modification,
+        ))
+    }
+
+    /// Maps a position of the environment into the window like it's drawn, see `render`.
+    fn window_position(
+        position: Position2D,
+        preferred_view: &Option<(Viewport2D, Viewport2DModification)>,
+        camera: &Camera,
+    ) -> [f64; 2] {
+        let (pref_viewport, _) = preferred_view.unwrap();
+        let transformation = Transformation2D::composition(
+            "Test".to_string(),
+            vec![
+                PistonVisualiser::viewport_transformation(
+                    &pref_viewport,
+                    &PistonVisualiser::window_viewport(),
+                ),
+                PistonVisualiser::camera_view_transformation(
+                    preferred_view,
+                    [0, 0, WINDOW_SIZE[0] as i32, WINDOW_SIZE[1] as i32],
+                    *camera,
+                )
+                .1,
+            ],
+        );
+        let normalized = position.transform(&transformation);
+        [
+            (normalized.x + 1f64) / 2f64 * WINDOW_SIZE[0],
+            (1f64 - normalized.y) / 2f64 * WINDOW_SIZE[1],
+        ]
+    }
+
+    fn assert_close(actual: Position2D, expected: Position2D) {
+        assert!(
+            (actual.x - expected.x).abs() < 1e-9 && (actual.y - expected.y).abs() < 1e-9,
+            "{:?} != {:?}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn maps_drawn_positions_back_into_the_environment() {
+        let camera = Camera {
+            zoom: 1.5f64,
+            offset: (0.2f64, -0.1f64),
+        };
+        for viewport_modification in [
+            Viewport2DModification::LooseAspectRatio,
+            Viewport2DModification::KeepAspectRatio,
+            Viewport2DModification::KeepAspectRatioAndScissorRemains,
+        ]
+        .iter()
+        {
+            let preferred_view = wide_view(*viewport_modification);
+            for position in [
+                Position2D::with(3f64, -2f64),
+                Position2D::with(4f64, -1.5f64),
+                Position2D::with(0.5f64, -3f64),
+            ]
+            .iter()
+            {
+                let mapped = environment_position(
+                    window_position(*position, &preferred_view, &camera),
+                    WINDOW_SIZE,
+                    &preferred_view,
+                    &camera,
+                );
+                assert_close(mapped.position, *position);
+                assert!(mapped.inside_view);
+            }
+        }
+    }
+
+    #[test]
+    fn excludes_positions_cut_off_by_the_scissor() {
+        let camera = Camera {
+            zoom: 0.5f64,
+            offset: (0f64, 0f64),
+        };
+        // The wide view only covers the middle half of the window vertically.
+        let scissored = wide_view(Viewport2DModification::KeepAspectRatioAndScissorRemains);
+        assert!(
+            !environment_position([200f64, 20f64], WINDOW_SIZE, &scissored, &camera).inside_view
+        );
+        assert!(
+            environment_position([200f64, 120f64], WINDOW_SIZE, &scissored, &camera).inside_view
+        );
+
+        let loose = wide_view(Viewport2DModification::LooseAspectRatio);
+        assert!(environment_position([200f64, 20f64], WINDOW_SIZE, &loose, &camera).inside_view);
+        assert!(!environment_position([-10f64, 200f64], WINDOW_SIZE, &loose, &camera).inside_view);
+    }
+}
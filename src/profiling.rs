@@ -0,0 +1,121 @@
+//! Per frame profiling of the render loop, which can also be drawn as an overlay.
+
+use std::time::Duration;
+
+use gymnarium_visualisers_base::Geometry2D;
+
+/* --- --- --- PrimitiveCounts --- --- --- */
+
+/// Number of drawn geometries per `Geometry2D` variant, counting the members of groups.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct PrimitiveCounts {
+    pub points: usize,
+    pub lines: usize,
+    pub polylines: usize,
+    pub triangles: usize,
+    pub squares: usize,
+    pub rectangles: usize,
+    pub polygons: usize,
+    pub circles: usize,
+    pub ellipses: usize,
+    pub images: usize,
+    pub groups: usize,
+    pub texts: usize,
+}
+
+impl PrimitiveCounts {
+    pub(crate) fn count(&mut self, geometry_2ds: &[Geometry2D]) {
+        geometry_2ds.iter().for_each(|geometry| match geometry {
+            Geometry2D::Point { .. } => self.points += 1,
+            Geometry2D::Line { .. } => self.lines += 1,
+            Geometry2D::Polyline { .. } => self.polylines += 1,
+            Geometry2D::Triangle { .. } => self.triangles += 1,
+            Geometry2D::Square { .. } => self.squares += 1,
+            Geometry2D::Rectangle { .. } => self.rectangles += 1,
+            Geometry2D::Polygon { .. } => self.polygons += 1,
+            Geometry2D::Circle { .. } => self.circles += 1,
+            Geometry2D::Ellipse { .. } => self.ellipses += 1,
+            Geometry2D::Image { .. } => self.images += 1,
+            Geometry2D::Group(geometries) => {
+                self.groups += 1;
+                self.count(geometries);
+            }
+        });
+    }
+
+    /// Number of drawn geometries without the groups themselves.
+    pub fn total(&self) -> usize {
+        self.points
+            + self.lines
+            + self.polylines
+            + self.triangles
+            + self.squares
+            + self.rectangles
+            + self.polygons
+            + self.circles
+            + self.ellipses
+            + self.images
+            + self.texts
+    }
+}
+
+/* --- --- --- FrameProfile --- --- --- */
+
+/// Timings and counts of a single frame of the render loop.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FrameProfile {
+    /// Time since the previous frame was drawn.
+    pub frame_interval: Duration,
+    /// Time the environment took to provide the drawn geometries, measured in
+    /// `render_two_dimensional`.
+    pub environment: Option<Duration>,
+    /// Loading and uploading textures in `update_texture_buffer`.
+    pub texture_update: Duration,
+    /// Loading fonts.
+    pub font_update: Duration,
+    /// Transforming the geometries and recording their draw calls.
+    pub geometry: Duration,
+    /// Handing the recorded draw calls over to the GPU.
+    pub submission: Duration,
+    /// Reading back the frame for captures and recordings.
+    pub capture: Duration,
+    pub primitives: PrimitiveCounts,
+    pub textures_loaded: usize,
+    pub textures_evicted: usize,
+}
+
+impl FrameProfile {
+    pub(crate) fn overlay_lines(&self) -> Vec<String> {
+        let milliseconds = |duration: Duration| duration.as_secs_f64() * 1000f64;
+        let frames_per_second = if self.frame_interval > Duration::default() {
+            1f64 / self.frame_interval.as_secs_f64()
+        } else {
+            0f64
+        };
+        vec![
+            format!(
+                "frame {:6.2} ms ({:5.1} fps)",
+                milliseconds(self.frame_interval),
+                frames_per_second
+            ),
+            match self.environment {
+                Some(environment) => format!("environment {:6.2} ms", milliseconds(environment)),
+                None => "environment      - ms".to_string(),
+            },
+            format!(
+                "textures {:6.2} ms (+{} -{})",
+                milliseconds(self.texture_update),
+                self.textures_loaded,
+                self.textures_evicted
+            ),
+            format!("fonts {:6.2} ms", milliseconds(self.font_update)),
+            format!(
+                "geometry {:6.2} ms ({} primitives)",
+                milliseconds(self.geometry),
+                self.primitives.total()
+            ),
+            format!("submission {:6.2} ms", milliseconds(self.submission)),
+            format!("capture {:6.2} ms", milliseconds(self.capture)),
+        ]
+    }
+}
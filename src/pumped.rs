@@ -0,0 +1,131 @@
+//! Visualiser which owns its window on the calling thread and is driven by the caller, e.g. to
+//! embed it into an existing loop.
+
+use std::error::Error;
+use std::time::Instant;
+
+use piston_window::Window;
+
+use gymnarium_visualisers_base::{
+    TwoDimensionalDrawableEnvironment, TwoDimensionalVisualiser, Visualiser,
+};
+
+use crate::{
+    Camera, Diagnostics, ExtendedTwoDimensionalDrawableEnvironment, FrameProfile,
+    FurtherPistonVisualiserError, PistonVisualiser, PistonVisualiserError,
+    PistonVisualiserInputProvider, PistonVisualiserSettings, RenderLoop,
+};
+
+/* --- --- --- PumpedPistonVisualiser --- --- --- */
+
+/// Draws into a window like the `PistonVisualiser`, but without a render thread.
+///
+/// `render_two_dimensional` only stores the data of the environment. The caller has to call
+/// `pump_events` regularly to handle the inputs of the window and `present` to draw the stored
+/// data.
+pub struct PumpedPistonVisualiser {
+    render_loop: RenderLoop,
+    input_provider: PistonVisualiserInputProvider,
+    diagnostics: Diagnostics,
+}
+
+impl PumpedPistonVisualiser {
+    pub fn new(settings: PistonVisualiserSettings) -> Result<Self, PistonVisualiserError> {
+        Ok(Self {
+            render_loop: RenderLoop::new(settings)
+                .map_err(PistonVisualiserError::WindowCreationFailed)?,
+            input_provider: PistonVisualiserInputProvider::default(),
+            diagnostics: Diagnostics::default(),
+        })
+    }
+
+    pub fn input_provider(&self) -> PistonVisualiserInputProvider {
+        self.input_provider.clone()
+    }
+
+    /// Returns and forgets the errors recovered from since the last call, like
+    /// `PistonVisualiser::take_diagnostics`.
+    pub fn take_diagnostics(&self) -> Vec<PistonVisualiserError> {
+        self.diagnostics.take()
+    }
+
+    /// Returns the timings and counts of the last presented frame.
+    pub fn last_frame_profile(&self) -> Option<FrameProfile> {
+        self.render_loop.profile
+    }
+
+    /// Shows or hides the profile of the last frame in the top left corner of the window.
+    pub fn set_profiling_overlay(&mut self, show: bool) {
+        self.render_loop.show_profiling_overlay = show;
+    }
+
+    /// Returns the current zoom and pan of the interactive camera.
+    pub fn camera(&self) -> Camera {
+        self.render_loop.camera_controller.camera
+    }
+
+    /// Handles all pending events of the window without waiting, the inputs end up in the
+    /// `input_provider`.
+    pub fn pump_events(&mut self) {
+        self.render_loop
+            .pump_events(&mut self.input_provider, &self.diagnostics);
+    }
+
+    /// Draws the data of the last rendered environment into the window.
+    pub fn present(&mut self) {
+        if self.is_open() {
+            self.render_loop.present(&self.diagnostics);
+        }
+    }
+
+    /// Stores the geometries and additionally the texts of the environment for the next
+    /// `present`.
+    pub fn render_extended_two_dimensional<
+        DrawableEnvironmentError: Error,
+        DrawableEnvironment: ExtendedTwoDimensionalDrawableEnvironment<DrawableEnvironmentError>,
+    >(
+        &mut self,
+        drawable_environment: &DrawableEnvironment,
+    ) -> Result<(), FurtherPistonVisualiserError<DrawableEnvironmentError>> {
+        let environment_started = Instant::now();
+        self.render_loop
+            .set_data(PistonVisualiser::extended_synced_data_of(
+                drawable_environment,
+            )?);
+        self.render_loop.environment_duration = Some(environment_started.elapsed());
+        Ok(())
+    }
+}
+
+impl Visualiser<PistonVisualiserError> for PumpedPistonVisualiser {
+    fn is_open(&self) -> bool {
+        !self.render_loop.window.should_close()
+    }
+
+    fn close(&mut self) -> Result<(), PistonVisualiserError> {
+        self.render_loop.window.set_should_close(true);
+        Ok(())
+    }
+}
+
+impl<DrawableEnvironmentError: Error + 'static>
+    TwoDimensionalVisualiser<
+        FurtherPistonVisualiserError<DrawableEnvironmentError>,
+        PistonVisualiserError,
+        DrawableEnvironmentError,
+    > for PumpedPistonVisualiser
+{
+    /// Stores the geometries of the environment for the next `present`.
+    fn render_two_dimensional<
+        DrawableEnvironment: TwoDimensionalDrawableEnvironment<DrawableEnvironmentError>,
+    >(
+        &mut self,
+        drawable_environment: &DrawableEnvironment,
+    ) -> Result<(), FurtherPistonVisualiserError<DrawableEnvironmentError>> {
+        let environment_started = Instant::now();
+        self.render_loop
+            .set_data(PistonVisualiser::synced_data_of(drawable_environment)?);
+        self.render_loop.environment_duration = Some(environment_started.elapsed());
+        Ok(())
+    }
+}
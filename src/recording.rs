@@ -0,0 +1,361 @@
+//! Recording of the frames presented by the `PistonVisualiser` into a single animated GIF or an
+//! uncompressed Y4M video.
+//!
+//! Encoding happens on a separate thread, so the render thread only has to read back the frames.
+
+use std::error::Error;
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, Sender, SyncSender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::imageops::FilterType;
+use image::{Delay, Frame, RgbaImage};
+
+/// Number of frames which may wait for the encoder before the render thread is blocked.
+const PENDING_FRAMES: usize = 16;
+
+/* --- --- --- EpisodeFormat --- --- --- */
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EpisodeFormat {
+    /// Animated GIF, which loops forever.
+    Gif,
+    /// Uncompressed YUV4MPEG2 stream (4:4:4, ITU-R BT.601), which e.g. ffmpeg can convert.
+    Y4m,
+}
+
+/* --- --- --- EpisodeRecorderSettings --- --- --- */
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EpisodeRecorderSettings {
+    path: PathBuf,
+    format: EpisodeFormat,
+    frames_per_second: u32,
+    scale: f64,
+    max_duration: Option<Duration>,
+}
+
+impl EpisodeRecorderSettings {
+    /// Records with 30 frames per second in the size of the window until the recording is stopped.
+    pub fn new<P: Into<PathBuf>>(path: P, format: EpisodeFormat) -> Self {
+        Self {
+            path: path.into(),
+            format,
+            frames_per_second: 30,
+            scale: 1f64,
+            max_duration: None,
+        }
+    }
+
+    /// Frame rate of the recording.
+    ///
+    /// The presented frames are sampled by wall clock time, so frames are skipped if the window
+    /// renders faster and repeated if it renders slower.
+    pub fn frames_per_second(mut self, frames_per_second: u32) -> Self {
+        self.frames_per_second = frames_per_second.max(1);
+        self
+    }
+
+    /// Scales the frames, e.g. `0.5` for half the window size.
+    pub fn scale(mut self, scale: f64) -> Self {
+        self.scale = scale.max(0f64);
+        self
+    }
+
+    /// Stops the recording automatically after the given duration of the recording.
+    pub fn max_duration(mut self, max_duration: Duration) -> Self {
+        self.max_duration = Some(max_duration);
+        self
+    }
+
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    pub fn format(&self) -> EpisodeFormat {
+        self.format
+    }
+
+    fn max_frames(&self) -> Option<u64> {
+        self.max_duration.map(|max_duration| {
+            (max_duration.as_secs_f64() * self.frames_per_second as f64).ceil() as u64
+        })
+    }
+}
+
+/* --- --- --- RecordedEpisode --- --- --- */
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedEpisode {
+    pub path: PathBuf,
+    /// Number of frames in the recording, including repeated frames.
+    pub frames: u64,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EpisodeRecordingError {
+    pub path: PathBuf,
+    pub cause: String,
+}
+
+impl Display for EpisodeRecordingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Could not record episode into {} (cause: {})",
+            self.path.display(),
+            self.cause
+        )
+    }
+}
+
+impl Error for EpisodeRecordingError {}
+
+/* --- --- --- EpisodeRecorder --- --- --- */
+
+pub(crate) struct EpisodeRecorder {
+    frames_per_second: u32,
+    max_frames: Option<u64>,
+    started: Option<Instant>,
+    recorded_frames: u64,
+    frame_sender: Option<SyncSender<(RgbaImage, u64)>>,
+}
+
+impl EpisodeRecorder {
+    /// Spawns the encoder thread, which reports the finished recording through `result_sender`
+    /// as soon as the recorder is dropped or the maximum duration is reached.
+    pub fn new(
+        settings: EpisodeRecorderSettings,
+        file: File,
+        result_sender: Sender<Result<RecordedEpisode, EpisodeRecordingError>>,
+    ) -> Self {
+        let (frame_sender, frame_receiver) = mpsc::sync_channel(PENDING_FRAMES);
+        let recorder = Self {
+            frames_per_second: settings.frames_per_second,
+            max_frames: settings.max_frames(),
+            started: None,
+            recorded_frames: 0,
+            frame_sender: Some(frame_sender),
+        };
+        thread::spawn(move || {
+            let path = settings.path.clone();
+            let result = Self::encode(settings, file, frame_receiver)
+                .map_err(|cause| EpisodeRecordingError { path, cause });
+            // The receiving side may have been dropped, if nobody waits for the recording.
+            let _ = result_sender.send(result);
+        });
+        recorder
+    }
+
+    /// Returns how often the frame presented now has to be recorded to keep up with the frame
+    /// rate, which is `0` if it can be skipped.
+    pub fn frames_due(&mut self) -> u64 {
+        if self.is_finished() {
+            return 0;
+        }
+        let started = *self.started.get_or_insert_with(Instant::now);
+        let mut target =
+            (started.elapsed().as_secs_f64() * self.frames_per_second as f64) as u64 + 1;
+        if let Some(max_frames) = self.max_frames {
+            target = target.min(max_frames);
+        }
+        target.saturating_sub(self.recorded_frames)
+    }
+
+    pub fn write(&mut self, frame: RgbaImage, repetitions: u64) {
+        if let Some(frame_sender) = &self.frame_sender {
+            if frame_sender.send((frame, repetitions)).is_err() {
+                // The encoder failed and already reported its error.
+                self.frame_sender = None;
+            }
+        }
+        self.recorded_frames += repetitions;
+        if self
+            .max_frames
+            .map(|max_frames| self.recorded_frames >= max_frames)
+            .unwrap_or(false)
+        {
+            // Closing the channel lets the encoder finish the file.
+            self.frame_sender = None;
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.frame_sender.is_none()
+    }
+
+    fn encode(
+        settings: EpisodeRecorderSettings,
+        file: File,
+        frame_receiver: Receiver<(RgbaImage, u64)>,
+    ) -> Result<RecordedEpisode, String> {
+        let mut writer = match settings.format {
+            EpisodeFormat::Gif => {
+                let mut encoder = GifEncoder::new(BufWriter::new(file));
+                encoder
+                    .set_repeat(Repeat::Infinite)
+                    .map_err(|e| format!("{}", e))?;
+                EpisodeWriter::Gif {
+                    encoder,
+                    pending: None,
+                }
+            }
+            EpisodeFormat::Y4m => EpisodeWriter::Y4m {
+                writer: BufWriter::new(file),
+                header_written: false,
+            },
+        };
+
+        // The size of the first frame is kept, even if the window is resized later on.
+        let mut size = None;
+        let mut frames = 0;
+        for (frame, repetitions) in frame_receiver {
+            let (width, height) = *size.get_or_insert_with(|| {
+                (
+                    ((frame.width() as f64 * settings.scale).round() as u32).max(1),
+                    ((frame.height() as f64 * settings.scale).round() as u32).max(1),
+                )
+            });
+            let frame = if frame.dimensions() == (width, height) {
+                frame
+            } else {
+                image::imageops::resize(&frame, width, height, FilterType::Triangle)
+            };
+            writer.write(frame, repetitions, settings.frames_per_second)?;
+            frames += repetitions;
+        }
+        writer.finish(settings.frames_per_second)?;
+
+        let (width, height) = size.unwrap_or((0, 0));
+        Ok(RecordedEpisode {
+            path: settings.path,
+            frames,
+            width,
+            height,
+        })
+    }
+}
+
+/* --- --- --- EpisodeWriter --- --- --- */
+
+enum EpisodeWriter {
+    Gif {
+        encoder: GifEncoder<BufWriter<File>>,
+        /// Repeated frames are merged into one GIF frame with a longer delay, so every frame is
+        /// held back until the next one arrives.
+        pending: Option<(RgbaImage, u64)>,
+    },
+    Y4m {
+        writer: BufWriter<File>,
+        header_written: bool,
+    },
+}
+
+impl EpisodeWriter {
+    fn write(
+        &mut self,
+        frame: RgbaImage,
+        repetitions: u64,
+        frames_per_second: u32,
+    ) -> Result<(), String> {
+        match self {
+            Self::Gif { encoder, pending } => {
+                if let Some((pending_frame, pending_repetitions)) =
+                    pending.replace((frame, repetitions))
+                {
+                    Self::encode_gif_frame(
+                        encoder,
+                        pending_frame,
+                        pending_repetitions,
+                        frames_per_second,
+                    )?;
+                }
+                Ok(())
+            }
+            Self::Y4m {
+                writer,
+                header_written,
+            } => {
+                if !*header_written {
+                    writeln!(
+                        writer,
+                        "YUV4MPEG2 W{} H{} F{}:1 Ip A1:1 C444",
+                        frame.width(),
+                        frame.height(),
+                        frames_per_second
+                    )
+                    .map_err(|e| format!("{}", e))?;
+                    *header_written = true;
+                }
+                let planes = Self::yuv_planes(&frame);
+                for _ in 0..repetitions {
+                    writer
+                        .write_all(b"FRAME\n")
+                        .and_then(|_| writer.write_all(&planes))
+                        .map_err(|e| format!("{}", e))?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn finish(self, frames_per_second: u32) -> Result<(), String> {
+        match self {
+            Self::Gif {
+                mut encoder,
+                pending,
+            } => {
+                if let Some((pending_frame, pending_repetitions)) = pending {
+                    Self::encode_gif_frame(
+                        &mut encoder,
+                        pending_frame,
+                        pending_repetitions,
+                        frames_per_second,
+                    )?;
+                }
+                // The trailer of the GIF is written when the encoder is dropped.
+                drop(encoder);
+                Ok(())
+            }
+            Self::Y4m { mut writer, .. } => writer.flush().map_err(|e| format!("{}", e)),
+        }
+    }
+
+    fn encode_gif_frame(
+        encoder: &mut GifEncoder<BufWriter<File>>,
+        frame: RgbaImage,
+        repetitions: u64,
+        frames_per_second: u32,
+    ) -> Result<(), String> {
+        let delay = Delay::from_numer_denom_ms(
+            (repetitions * 1000).min(u32::MAX as u64) as u32,
+            frames_per_second,
+        );
+        encoder
+            .encode_frame(Frame::from_parts(frame, 0, 0, delay))
+            .map_err(|e| format!("{}", e))
+    }
+
+    /// Converts the frame into the Y, U and V planes with the studio swing of ITU-R BT.601.
+    fn yuv_planes(frame: &RgbaImage) -> Vec<u8> {
+        let pixel_count = frame.width() as usize * frame.height() as usize;
+        let mut planes = vec![0u8; pixel_count * 3];
+        for (index, pixel) in frame.pixels().enumerate() {
+            let (r, g, b) = (pixel[0] as f64, pixel[1] as f64, pixel[2] as f64);
+            let y = 16f64 + (65.738 * r + 129.057 * g + 25.064 * b) / 256f64;
+            let u = 128f64 + (-37.945 * r - 74.494 * g + 112.439 * b) / 256f64;
+            let v = 128f64 + (112.439 * r - 94.154 * g - 18.285 * b) / 256f64;
+            planes[index] = y.round() as u8;
+            planes[pixel_count + index] = u.round() as u8;
+            planes[2 * pixel_count + index] = v.round() as u8;
+        }
+        planes
+    }
+}
@@ -0,0 +1,128 @@
+//! Retained scene of geometries, which are inserted once and afterwards only updated or removed
+//! through their handles, so only the changes are handed over to the render thread.
+
+use std::collections::BTreeMap;
+
+use piston_window::DrawState;
+
+use gymnarium_base::math::{Position2D, Size2D};
+use gymnarium_visualisers_base::{Geometry2D, Viewport2D, Viewport2DModification};
+
+use crate::triangulation::PolygonFills;
+use crate::{triangulate_polygons, Camera, Diagnostics, PistonVisualiser};
+
+/* --- --- --- GeometryHandle --- --- --- */
+
+/// Identifies a geometry inserted into the retained scene of a `PistonVisualiser`.
+///
+/// Handles are never reused, so a handle of a removed geometry stays invalid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GeometryHandle(pub(crate) u64);
+
+/* --- --- --- SceneChange --- --- --- */
+
+/// Change of the retained scene sent to the render thread.
+pub(crate) enum SceneChange {
+    /// Inserts or replaces the geometry with the given handle.
+    Set(GeometryHandle, Geometry2D),
+    Remove(GeometryHandle),
+    Clear,
+    View(Option<(Viewport2D, Viewport2DModification)>),
+}
+
+/* --- --- --- RetainedScene --- --- --- */
+
+/// Retained scene as kept by the render loop.
+///
+/// Every geometry is transformed into the viewport of the window once and kept transformed
+/// until it's changed, the view of the scene or the camera changes or the window is resized.
+#[derive(Default)]
+pub(crate) struct RetainedScene {
+    view: Option<(Viewport2D, Viewport2DModification)>,
+    /// The geometries as given with their triangulated polygons and, once drawn, transformed into
+    /// the viewport of the window.
+    geometries: BTreeMap<GeometryHandle, (Geometry2D, PolygonFills, Option<Geometry2D>)>,
+    /// Viewport and camera the geometries were last transformed for.
+    transformed_for: Option<([i32; 4], Camera)>,
+    draw_state: DrawState,
+}
+
+impl RetainedScene {
+    /// Applies the change, polygons which can't be triangulated are reported to `diagnostics`.
+    pub fn apply(&mut self, change: SceneChange, diagnostics: &Diagnostics) {
+        match change {
+            SceneChange::Set(handle, geometry) => {
+                let polygon_fills =
+                    triangulate_polygons(std::slice::from_ref(&geometry), diagnostics);
+                self.geometries
+                    .insert(handle, (geometry, polygon_fills, None));
+            }
+            SceneChange::Remove(handle) => {
+                self.geometries.remove(&handle);
+            }
+            SceneChange::Clear => self.geometries.clear(),
+            SceneChange::View(view) => {
+                self.view = view;
+                self.transformed_for = None;
+            }
+        }
+    }
+
+    /// The geometries in the order they were inserted, as given.
+    pub fn geometries(&self) -> impl Iterator<Item = &Geometry2D> {
+        self.geometries.values().map(|(geometry, _, _)| geometry)
+    }
+
+    /// Transforms the changed geometries, or all of them if the viewport or camera changed, into
+    /// the viewport of the window.
+    pub fn prepare(&mut self, viewport_rect: [i32; 4], camera: Camera) {
+        if self.transformed_for != Some((viewport_rect, camera)) {
+            let (scissor, _) = PistonVisualiser::view_transformation(&self.view, viewport_rect);
+            self.draw_state = if let Some(scissor) = scissor {
+                DrawState::default().scissor(scissor)
+            } else {
+                DrawState::default()
+            };
+            self.geometries
+                .values_mut()
+                .for_each(|(_, _, transformed)| *transformed = None);
+            self.transformed_for = Some((viewport_rect, camera));
+        }
+        let pref_viewport = if let Some((pref_viewport, _)) = self.view {
+            pref_viewport
+        } else {
+            Viewport2D::with(Position2D::zero(), Size2D::with(2f64, 2f64))
+        };
+        let (_, transform) =
+            PistonVisualiser::camera_view_transformation(&self.view, viewport_rect, camera);
+        self.geometries
+            .values_mut()
+            .filter(|(_, _, transformed)| transformed.is_none())
+            .for_each(|(geometry, _, transformed)| {
+                *transformed = Some(
+                    geometry
+                        .clone()
+                        .transform(&pref_viewport, &PistonVisualiser::window_viewport())
+                        .append_transformation(transform.clone()),
+                );
+            });
+    }
+
+    /// The geometries transformed by the last `prepare` with their handles and triangulated
+    /// polygons.
+    pub fn transformed_geometries(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = (GeometryHandle, &Geometry2D, &PolygonFills)> {
+        self.geometries
+            .iter()
+            .filter_map(|(handle, (_, polygon_fills, transformed))| {
+                transformed
+                    .as_ref()
+                    .map(|transformed| (*handle, transformed, polygon_fills))
+            })
+    }
+
+    pub fn draw_state(&self) -> &DrawState {
+        &self.draw_state
+    }
+}
@@ -0,0 +1,192 @@
+//! Settings of the window opened by the `PistonVisualiser`.
+
+use std::time::Duration;
+
+use gymnarium_visualisers_base::Color;
+
+/* --- --- --- RenderMode --- --- --- */
+
+/// Decides how `render_two_dimensional` hands the frames to the render thread.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum RenderMode {
+    /// Returns immediately. The render thread only presents the latest frame, so frames are
+    /// skipped if the environment is rendered faster than the window.
+    #[default]
+    Latest,
+    /// Blocks until the render thread presented the frame, so every frame is shown and e.g.
+    /// captured. Fails if that takes longer than the timeout or the window is closed before.
+    Lockstep { timeout: Option<Duration> },
+}
+
+/* --- --- --- TextureCachePolicy --- --- --- */
+
+/// Decides how long loaded textures are kept on the GPU.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TextureCachePolicy {
+    /// Drops a texture after it wasn't drawn for roughly the given number of frames.
+    DropUnusedAfterFrames(usize),
+    /// Keeps every texture until the window is closed.
+    KeepForever,
+}
+
+impl Default for TextureCachePolicy {
+    fn default() -> Self {
+        Self::DropUnusedAfterFrames(180)
+    }
+}
+
+/* --- --- --- PistonVisualiserSettings --- --- --- */
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PistonVisualiserSettings {
+    pub(crate) window_title: String,
+    pub(crate) window_dimension: (u32, u32),
+    pub(crate) max_frames_per_second: Option<u64>,
+    pub(crate) resizable: bool,
+    pub(crate) vsync: bool,
+    pub(crate) samples: u8,
+    pub(crate) decorated: bool,
+    pub(crate) fullscreen: bool,
+    pub(crate) minimum_size: Option<(u32, u32)>,
+    pub(crate) exit_on_esc: bool,
+    pub(crate) background_color: Option<Color>,
+    pub(crate) texture_cache_policy: TextureCachePolicy,
+    pub(crate) reopen_when_closed: bool,
+    pub(crate) render_mode: RenderMode,
+    pub(crate) profiling_overlay: bool,
+    pub(crate) interactive_camera: bool,
+    pub(crate) pick_events: bool,
+    pub(crate) tooltips: bool,
+    pub(crate) playback_hotkeys: bool,
+}
+
+impl PistonVisualiserSettings {
+    /// Creates the settings `PistonVisualiser::run` uses: a resizable and decorated window
+    /// without vsync and multisampling, which is closed when pressing escape.
+    pub fn new(window_title: String, window_dimension: (u32, u32)) -> Self {
+        Self {
+            window_title,
+            window_dimension,
+            max_frames_per_second: None,
+            resizable: true,
+            vsync: false,
+            samples: 0,
+            decorated: true,
+            fullscreen: false,
+            minimum_size: None,
+            exit_on_esc: true,
+            background_color: None,
+            texture_cache_policy: TextureCachePolicy::default(),
+            reopen_when_closed: false,
+            render_mode: RenderMode::default(),
+            profiling_overlay: false,
+            interactive_camera: false,
+            pick_events: false,
+            tooltips: false,
+            playback_hotkeys: true,
+        }
+    }
+
+    pub fn max_frames_per_second(mut self, max_frames_per_second: u64) -> Self {
+        self.max_frames_per_second = Some(max_frames_per_second);
+        self
+    }
+
+    pub fn resizable(mut self, resizable: bool) -> Self {
+        self.resizable = resizable;
+        self
+    }
+
+    pub fn vsync(mut self, vsync: bool) -> Self {
+        self.vsync = vsync;
+        self
+    }
+
+    /// Number of samples used for multisample anti-aliasing, `0` disables it.
+    pub fn samples(mut self, samples: u8) -> Self {
+        self.samples = samples;
+        self
+    }
+
+    pub fn decorated(mut self, decorated: bool) -> Self {
+        self.decorated = decorated;
+        self
+    }
+
+    pub fn fullscreen(mut self, fullscreen: bool) -> Self {
+        self.fullscreen = fullscreen;
+        self
+    }
+
+    /// The window is enlarged again whenever it's resized below this size.
+    pub fn minimum_size(mut self, minimum_size: (u32, u32)) -> Self {
+        self.minimum_size = Some(minimum_size);
+        self
+    }
+
+    pub fn exit_on_esc(mut self, exit_on_esc: bool) -> Self {
+        self.exit_on_esc = exit_on_esc;
+        self
+    }
+
+    /// Background color used as long as the environment doesn't prefer one.
+    pub fn background_color(mut self, background_color: Color) -> Self {
+        self.background_color = Some(background_color);
+        self
+    }
+
+    pub fn texture_cache_policy(mut self, texture_cache_policy: TextureCachePolicy) -> Self {
+        self.texture_cache_policy = texture_cache_policy;
+        self
+    }
+
+    /// Opens the window again with the next rendered frame, after the user closed it.
+    ///
+    /// Windows closed by `close` or by a panic of the render thread are not reopened.
+    pub fn reopen_when_closed(mut self, reopen_when_closed: bool) -> Self {
+        self.reopen_when_closed = reopen_when_closed;
+        self
+    }
+
+    pub fn render_mode(mut self, render_mode: RenderMode) -> Self {
+        self.render_mode = render_mode;
+        self
+    }
+
+    /// Shows the timings of the last frame in the top left corner of the window from the start.
+    pub fn profiling_overlay(mut self, profiling_overlay: bool) -> Self {
+        self.profiling_overlay = profiling_overlay;
+        self
+    }
+
+    /// Lets the user zoom with the mouse wheel, pan by dragging with the right mouse button and
+    /// reset the camera with the home key. The inputs still end up in the input provider.
+    /// Disabled by default.
+    pub fn interactive_camera(mut self, interactive_camera: bool) -> Self {
+        self.interactive_camera = interactive_camera;
+        self
+    }
+
+    /// Pushes a `PickEvent` into the input provider whenever the geometry under the cursor
+    /// changes or a mouse button is pressed.
+    pub fn pick_events(mut self, pick_events: bool) -> Self {
+        self.pick_events = pick_events;
+        self
+    }
+
+    /// Shows the metadata of the hovered geometry next to the cursor, if the environment
+    /// provides metadata with `draw_metadata`. Disabled by default.
+    pub fn tooltips(mut self, tooltips: bool) -> Self {
+        self.tooltips = tooltips;
+        self
+    }
+
+    /// Lets the user pause with space, single-step with `.` and change the requested speed with
+    /// `+` and `-`. These only take effect if the simulation calls
+    /// `PistonVisualiser::wait_while_paused` and respects `requested_speed`. Enabled by default,
+    /// disable it if the environment uses these keys itself.
+    pub fn playback_hotkeys(mut self, playback_hotkeys: bool) -> Self {
+        self.playback_hotkeys = playback_hotkeys;
+        self
+    }
+}
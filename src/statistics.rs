@@ -0,0 +1,55 @@
+//! Statistics about handing frames from `render_two_dimensional` over to the render thread.
+
+use std::time::Duration;
+
+/* --- --- --- HandoffStatistics --- --- --- */
+
+/// Snapshot of the counters and timings of a `PistonVisualiser`.
+///
+/// Every call to `render_two_dimensional` is counted as submitted. It's either deduplicated,
+/// because nothing changed since the last frame, or handed over to the render thread, where
+/// it's either presented or dropped, because a newer frame replaced it before it was presented.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct HandoffStatistics {
+    pub frames_submitted: u64,
+    pub frames_deduplicated: u64,
+    pub frames_dropped: u64,
+    /// Frames which were presented at least once, each frame is only counted once.
+    pub frames_presented: u64,
+    /// Time from submitting to first presenting the last presented frame.
+    pub last_latency: Option<Duration>,
+    pub max_latency: Option<Duration>,
+    /// Sum of the latencies of all presented frames.
+    pub total_latency: Duration,
+}
+
+impl HandoffStatistics {
+    pub fn mean_latency(&self) -> Option<Duration> {
+        if self.frames_presented == 0 {
+            None
+        } else {
+            Some(self.total_latency / self.frames_presented.min(u32::MAX as u64) as u32)
+        }
+    }
+
+    pub(crate) fn record_presented(&mut self, latency: Duration) {
+        self.frames_presented += 1;
+        self.last_latency = Some(latency);
+        self.max_latency = Some(self.max_latency.map_or(latency, |max| max.max(latency)));
+        self.total_latency += latency;
+    }
+
+    /// Adds the counters and timings recorded in `other` since this snapshot was taken.
+    pub(crate) fn merge(&mut self, other: &HandoffStatistics) {
+        self.frames_submitted += other.frames_submitted;
+        self.frames_deduplicated += other.frames_deduplicated;
+        self.frames_dropped += other.frames_dropped;
+        self.frames_presented += other.frames_presented;
+        self.last_latency = other.last_latency.or(self.last_latency);
+        self.max_latency = match (self.max_latency, other.max_latency) {
+            (Some(max), Some(other_max)) => Some(max.max(other_max)),
+            (max, other_max) => max.or(other_max),
+        };
+        self.total_latency += other.total_latency;
+    }
+}
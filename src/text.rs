@@ -0,0 +1,163 @@
+//! Text primitive, which is drawn alongside the geometries of an environment.
+//!
+//! `Geometry2D` has no variant for text, so texts are provided separately through
+//! `ExtendedTwoDimensionalDrawableEnvironment::draw_texts`.
+
+use std::hash::{Hash, Hasher};
+
+use gymnarium_base::math::{Position2D, Transformation2D};
+use gymnarium_visualisers_base::{Color, Viewport2D};
+
+use crate::PistonVisualiser;
+
+/// DejaVu Sans Mono, which is used whenever `FontSource::Default` is requested.
+pub(crate) const DEFAULT_FONT: &[u8] = include_bytes!("../assets/fonts/DejaVuSansMono.ttf");
+
+/* --- --- --- FontSource --- --- --- */
+
+#[derive(Debug, Clone, Default)]
+pub enum FontSource {
+    /// The font embedded into this crate.
+    #[default]
+    Default,
+    /// Path to a TrueType or OpenType font file.
+    Path(String),
+    /// Content of a TrueType or OpenType font file, e.g. from `include_bytes!`.
+    Bytes(&'static [u8]),
+}
+
+impl FontSource {
+    pub(crate) fn bytes(&self) -> Option<&'static [u8]> {
+        match self {
+            Self::Default => Some(DEFAULT_FONT),
+            Self::Path(_) => None,
+            Self::Bytes(bytes) => Some(bytes),
+        }
+    }
+}
+
+// Embedded fonts are compared by their address to avoid hashing the whole font every frame.
+impl PartialEq for FontSource {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Default, Self::Default) => true,
+            (Self::Path(a), Self::Path(b)) => a == b,
+            (Self::Bytes(a), Self::Bytes(b)) => std::ptr::eq(*a, *b),
+            _ => false,
+        }
+    }
+}
+
+impl Eq for FontSource {}
+
+impl Hash for FontSource {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            Self::Default => {}
+            Self::Path(path) => path.hash(state),
+            Self::Bytes(bytes) => {
+                bytes.as_ptr().hash(state);
+                bytes.len().hash(state);
+            }
+        }
+    }
+}
+
+/* --- --- --- TextAnchor --- --- --- */
+
+/// Point of the text's bounding box which is placed at the text's position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TextAnchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    #[default]
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl TextAnchor {
+    /// Returns the offset in pixels from the anchor to the start of the text's baseline.
+    pub(crate) fn offset(&self, text_width: f64, font_size: u32) -> [f64; 2] {
+        let font_size = font_size as f64;
+        let x = match self {
+            Self::TopLeft | Self::Left | Self::BottomLeft => 0f64,
+            Self::Top | Self::Center | Self::Bottom => -text_width / 2f64,
+            Self::TopRight | Self::Right | Self::BottomRight => -text_width,
+        };
+        let y = match self {
+            Self::TopLeft | Self::Top | Self::TopRight => font_size,
+            Self::Left | Self::Center | Self::Right => font_size / 2f64,
+            Self::BottomLeft | Self::Bottom | Self::BottomRight => 0f64,
+        };
+        [x, y]
+    }
+}
+
+/* --- --- --- Text2D --- --- --- */
+
+/// A single line of text.
+///
+/// Only the position is affected by the transformations, the glyphs are always drawn upright
+/// with `font_size` pixels in screen space.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Text2D {
+    pub position: Position2D,
+    pub text: String,
+    pub font: FontSource,
+    pub font_size: u32,
+    pub color: Color,
+    pub anchor: TextAnchor,
+    pub transformations: Transformation2D,
+}
+
+impl Text2D {
+    pub fn new(position: Position2D, text: String, font_size: u32, color: Color) -> Self {
+        Self {
+            position,
+            text,
+            font: FontSource::default(),
+            font_size,
+            color,
+            anchor: TextAnchor::default(),
+            transformations: Transformation2D::identity(),
+        }
+    }
+
+    pub fn font(mut self, font: FontSource) -> Self {
+        self.font = font;
+        self
+    }
+
+    pub fn anchor(mut self, anchor: TextAnchor) -> Self {
+        self.anchor = anchor;
+        self
+    }
+
+    pub fn append_transformation(mut self, transformation: Transformation2D) -> Self {
+        self.transformations = Transformation2D::composition(
+            "Text2D".to_string(),
+            vec![self.transformations, transformation],
+        );
+        self
+    }
+
+    /// Transforms the text from one viewport into another, like `Geometry2D::transform`.
+    pub fn transform(self, from: &Viewport2D, to: &Viewport2D) -> Self {
+        self.append_transformation(PistonVisualiser::viewport_transformation(from, to))
+    }
+
+    /// Returns the anchor position in pixels for a window or image of the given size.
+    pub(crate) fn pixel_position(&self, size: [f64; 2]) -> [f64; 2] {
+        let transformed = self.position.transform(&self.transformations);
+        [
+            (transformed.x + 1f64) / 2f64 * size[0],
+            (1f64 - transformed.y) / 2f64 * size[1],
+        ]
+    }
+}
@@ -0,0 +1,645 @@
+//! Triangulation of arbitrary simple polygons, optionally with holes, by ear clipping.
+//!
+//! Holes are merged into the outline by bridging each hole to a visible vertex of the outline
+//! (see David Eberly, "Triangulation by Ear Clipping"), after which the resulting polygon is
+//! clipped ear by ear.
+
+use std::error::Error;
+use std::fmt::Display;
+
+use gymnarium_base::math::{Position2D, Transformation2D};
+use gymnarium_visualisers_base::{Color, Geometry2D, LineShape};
+
+type Point = [f64; 2];
+
+const EPSILON: f64 = 1e-12;
+
+/* --- --- --- TriangulationError --- --- --- */
+
+/// Rings are counted with the outline as ring `0` and the holes as rings `1..`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TriangulationError {
+    TooFewPoints {
+        ring: usize,
+        points: usize,
+    },
+    SelfIntersecting {
+        ring: usize,
+        edge: usize,
+        other_ring: usize,
+        other_edge: usize,
+    },
+    HoleOutsideOutline {
+        ring: usize,
+    },
+}
+
+impl Display for TriangulationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TooFewPoints { ring, points } => write!(
+                f,
+                "Ring {} of the polygon has only {} distinct points, but at least 3 are needed",
+                ring, points
+            ),
+            Self::SelfIntersecting {
+                ring,
+                edge,
+                other_ring,
+                other_edge,
+            } => write!(
+                f,
+                "The polygon is self-intersecting: edge {} of ring {} crosses edge {} of ring {}",
+                edge, ring, other_edge, other_ring
+            ),
+            Self::HoleOutsideOutline { ring } => {
+                write!(
+                    f,
+                    "Hole (ring {}) lies outside of the polygon outline",
+                    ring
+                )
+            }
+        }
+    }
+}
+
+impl Error for TriangulationError {}
+
+/* --- --- --- triangulate --- --- --- */
+
+/// Splits the polygon described by `outline` and `holes` into triangles.
+///
+/// The rings may be given in any orientation, but must not intersect themselves or each other.
+pub fn triangulate(
+    outline: &[Point],
+    holes: &[Vec<Point>],
+) -> Result<Vec<[Point; 3]>, TriangulationError> {
+    let mut rings = Vec::with_capacity(holes.len() + 1);
+    for (ring, points) in std::iter::once(outline)
+        .chain(holes.iter().map(|hole| hole.as_slice()))
+        .enumerate()
+    {
+        let cleaned = without_duplicates(points);
+        if cleaned.len() < 3 {
+            return Err(TriangulationError::TooFewPoints {
+                ring,
+                points: cleaned.len(),
+            });
+        }
+        rings.push(cleaned);
+    }
+    check_self_intersections(&rings)?;
+    // The rings don't cross each other, so a hole lies inside the outline if any of its points
+    // does.
+    if let Some(ring) = (1..rings.len()).find(|ring| !contains(&rings[0], rings[*ring][0])) {
+        return Err(TriangulationError::HoleOutsideOutline { ring });
+    }
+
+    let mut rings = rings.into_iter();
+    let mut polygon = rings.next().unwrap();
+    if signed_area(&polygon) < 0f64 {
+        polygon.reverse();
+    }
+    let mut holes = rings
+        .enumerate()
+        .map(|(index, mut hole)| {
+            if signed_area(&hole) > 0f64 {
+                hole.reverse();
+            }
+            (index + 1, hole)
+        })
+        .collect::<Vec<(usize, Vec<Point>)>>();
+    holes.sort_by(|(_, a), (_, b)| {
+        max_x(b)
+            .partial_cmp(&max_x(a))
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    for (ring, hole) in holes {
+        polygon =
+            bridge_hole(polygon, &hole).ok_or(TriangulationError::HoleOutsideOutline { ring })?;
+    }
+
+    Ok(clip_ears(&polygon))
+}
+
+/// Creates a `Geometry2D::Group` of triangles filling the polygon with holes and of closed
+/// polylines outlining the outline and every hole.
+///
+/// `Geometry2D::Polygon` can't describe holes, so this group can be used instead.
+pub fn polygon_with_holes(
+    outline: &[Position2D],
+    holes: &[Vec<Position2D>],
+    fill_color: Color,
+    border_color: Color,
+    border_width: f64,
+    transformations: Transformation2D,
+) -> Result<Geometry2D, TriangulationError> {
+    let as_points = |positions: &[Position2D]| -> Vec<Point> {
+        positions
+            .iter()
+            .map(|position| [position.x, position.y])
+            .collect()
+    };
+    let triangles = triangulate(
+        &as_points(outline),
+        &holes
+            .iter()
+            .map(|hole| as_points(hole))
+            .collect::<Vec<Vec<Point>>>(),
+    )?;
+
+    let mut geometries = triangles
+        .into_iter()
+        .map(|triangle| Geometry2D::Triangle {
+            points: [
+                Position2D::with(triangle[0][0], triangle[0][1]),
+                Position2D::with(triangle[1][0], triangle[1][1]),
+                Position2D::with(triangle[2][0], triangle[2][1]),
+            ],
+            fill_color,
+            border_color: fill_color,
+            border_width: 0f64,
+            transformations: transformations.clone(),
+        })
+        .collect::<Vec<Geometry2D>>();
+    for ring in std::iter::once(outline).chain(holes.iter().map(|hole| hole.as_slice())) {
+        let mut points = ring.to_vec();
+        if let Some(first) = ring.first() {
+            points.push(*first);
+        }
+        geometries.push(Geometry2D::Polyline {
+            points,
+            line_color: border_color,
+            line_width: border_width,
+            line_shape: LineShape::Round,
+            transformations: transformations.clone(),
+        });
+    }
+    Ok(Geometry2D::Group(geometries))
+}
+
+/* --- --- --- PolygonFills --- --- --- */
+
+/// Iterator over the triangles of the polygons in `PolygonFills`.
+pub(crate) type PolygonFillsIter<'a> = std::slice::Iter<'a, Vec<[Point; 3]>>;
+
+/// Triangles filling the `Geometry2D::Polygon`s among some geometries, in the order they are
+/// drawn, so every polygon is triangulated once instead of on every drawn frame.
+#[derive(Default)]
+pub(crate) struct PolygonFills {
+    fills: Vec<Vec<[Point; 3]>>,
+}
+
+impl PolygonFills {
+    /// Triangulates the polygons, also those inside groups. Polygons which can't be triangulated
+    /// get no triangles and their errors are returned.
+    pub fn of(geometry_2ds: &[Geometry2D]) -> (Self, Vec<TriangulationError>) {
+        let mut polygon_fills = Self::default();
+        let mut errors = Vec::new();
+        polygon_fills.push_all(geometry_2ds, &mut errors);
+        (polygon_fills, errors)
+    }
+
+    fn push_all(&mut self, geometry_2ds: &[Geometry2D], errors: &mut Vec<TriangulationError>) {
+        for geometry in geometry_2ds {
+            match geometry {
+                Geometry2D::Polygon { points, .. } => {
+                    let polygon = points
+                        .iter()
+                        .map(|position| [position.x, position.y])
+                        .collect::<Vec<Point>>();
+                    self.fills
+                        .push(triangulate(&polygon, &[]).unwrap_or_else(|error| {
+                            errors.push(error);
+                            Vec::new()
+                        }));
+                }
+                Geometry2D::Group(geometries) => self.push_all(geometries, errors),
+                _ => {}
+            }
+        }
+    }
+
+    /// Iterator over the triangles of every polygon, which is advanced once per drawn polygon.
+    pub fn iter(&self) -> PolygonFillsIter<'_> {
+        self.fills.iter()
+    }
+}
+
+/* --- --- --- helpers --- --- --- */
+
+fn cross(origin: Point, a: Point, b: Point) -> f64 {
+    (a[0] - origin[0]) * (b[1] - origin[1]) - (a[1] - origin[1]) * (b[0] - origin[0])
+}
+
+fn signed_area(ring: &[Point]) -> f64 {
+    (0..ring.len())
+        .map(|index| {
+            let (a, b) = (ring[index], ring[(index + 1) % ring.len()]);
+            a[0] * b[1] - b[0] * a[1]
+        })
+        .sum::<f64>()
+        / 2f64
+}
+
+/// Whether the point lies inside the ring (even-odd rule).
+fn contains(ring: &[Point], point: Point) -> bool {
+    let mut inside = false;
+    for index in 0..ring.len() {
+        let (a, b) = (ring[index], ring[(index + 1) % ring.len()]);
+        if (a[1] > point[1]) != (b[1] > point[1])
+            && point[0] < (b[0] - a[0]) * (point[1] - a[1]) / (b[1] - a[1]) + a[0]
+        {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+fn max_x(ring: &[Point]) -> f64 {
+    ring.iter().map(|p| p[0]).fold(f64::NEG_INFINITY, f64::max)
+}
+
+fn without_duplicates(ring: &[Point]) -> Vec<Point> {
+    let mut cleaned: Vec<Point> = Vec::with_capacity(ring.len());
+    for point in ring {
+        if cleaned.last() != Some(point) {
+            cleaned.push(*point);
+        }
+    }
+    while cleaned.len() > 1 && cleaned.first() == cleaned.last() {
+        let _ = cleaned.pop();
+    }
+    cleaned
+}
+
+fn on_segment(a: Point, b: Point, p: Point) -> bool {
+    p[0] >= a[0].min(b[0])
+        && p[0] <= a[0].max(b[0])
+        && p[1] >= a[1].min(b[1])
+        && p[1] <= a[1].max(b[1])
+}
+
+fn segments_intersect(a1: Point, a2: Point, b1: Point, b2: Point) -> bool {
+    let d1 = cross(b1, b2, a1);
+    let d2 = cross(b1, b2, a2);
+    let d3 = cross(a1, a2, b1);
+    let d4 = cross(a1, a2, b2);
+    if ((d1 > 0f64 && d2 < 0f64) || (d1 < 0f64 && d2 > 0f64))
+        && ((d3 > 0f64 && d4 < 0f64) || (d3 < 0f64 && d4 > 0f64))
+    {
+        return true;
+    }
+    (d1 == 0f64 && on_segment(b1, b2, a1))
+        || (d2 == 0f64 && on_segment(b1, b2, a2))
+        || (d3 == 0f64 && on_segment(a1, a2, b1))
+        || (d4 == 0f64 && on_segment(a1, a2, b2))
+}
+
+fn check_self_intersections(rings: &[Vec<Point>]) -> Result<(), TriangulationError> {
+    let edges = rings
+        .iter()
+        .enumerate()
+        .flat_map(|(ring, points)| {
+            (0..points.len())
+                .map(move |edge| (ring, edge, points[edge], points[(edge + 1) % points.len()]))
+        })
+        .collect::<Vec<(usize, usize, Point, Point)>>();
+    for (index, (ring, edge, a1, a2)) in edges.iter().enumerate() {
+        for (other_ring, other_edge, b1, b2) in &edges[(index + 1)..] {
+            if ring == other_ring {
+                let ring_len = rings[*ring].len();
+                let adjacent =
+                    *other_edge == edge + 1 || (*edge == 0 && *other_edge == ring_len - 1);
+                if adjacent {
+                    continue;
+                }
+            }
+            if segments_intersect(*a1, *a2, *b1, *b2) {
+                return Err(TriangulationError::SelfIntersecting {
+                    ring: *ring,
+                    edge: *edge,
+                    other_ring: *other_ring,
+                    other_edge: *other_edge,
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+fn in_triangle(a: Point, b: Point, c: Point, p: Point) -> bool {
+    cross(a, b, p) >= -EPSILON && cross(b, c, p) >= -EPSILON && cross(c, a, p) >= -EPSILON
+}
+
+/// Connects the (clockwise) hole to the (counter clockwise) polygon with two coincident edges.
+fn bridge_hole(polygon: Vec<Point>, hole: &[Point]) -> Option<Vec<Point>> {
+    let hole_index = (0..hole.len()).max_by(|a, b| {
+        hole[*a][0]
+            .partial_cmp(&hole[*b][0])
+            .unwrap_or(std::cmp::Ordering::Equal)
+    })?;
+    let m = hole[hole_index];
+
+    // Find the nearest edge hit by a ray from m in positive x direction.
+    let mut nearest: Option<(f64, usize)> = None;
+    for index in 0..polygon.len() {
+        let (a, b) = (polygon[index], polygon[(index + 1) % polygon.len()]);
+        if (a[1] - b[1]).abs() < EPSILON || m[1] < a[1].min(b[1]) || m[1] > a[1].max(b[1]) {
+            continue;
+        }
+        let x = a[0] + (m[1] - a[1]) * (b[0] - a[0]) / (b[1] - a[1]);
+        let closer = match nearest {
+            Some((nearest_x, _)) => x < nearest_x,
+            None => true,
+        };
+        if x >= m[0] && closer {
+            nearest = Some((x, index));
+        }
+    }
+    let (intersection_x, edge_index) = nearest?;
+    let intersection = [intersection_x, m[1]];
+    let (a_index, b_index) = (edge_index, (edge_index + 1) % polygon.len());
+    let mut p_index = if polygon[a_index][0] > polygon[b_index][0] {
+        a_index
+    } else {
+        b_index
+    };
+
+    // A reflex vertex inside the triangle (m, intersection, p) could hide p from m, then the
+    // one with the smallest angle to the ray is visible instead.
+    if polygon[p_index] != intersection {
+        let p = polygon[p_index];
+        let mut best_angle = f64::INFINITY;
+        for index in 0..polygon.len() {
+            let prev = polygon[(index + polygon.len() - 1) % polygon.len()];
+            let (current, next) = (polygon[index], polygon[(index + 1) % polygon.len()]);
+            let reflex = cross(prev, current, next) <= 0f64;
+            if index == p_index || !reflex || current[0] < m[0] {
+                continue;
+            }
+            let inside = if p[1] >= m[1] {
+                in_triangle(m, intersection, p, current)
+            } else {
+                in_triangle(m, p, intersection, current)
+            };
+            if inside {
+                let angle = (current[1] - m[1]).abs().atan2(current[0] - m[0]);
+                if angle < best_angle {
+                    best_angle = angle;
+                    p_index = index;
+                }
+            }
+        }
+    }
+
+    let mut bridged = Vec::with_capacity(polygon.len() + hole.len() + 2);
+    bridged.extend_from_slice(&polygon[..=p_index]);
+    bridged.extend_from_slice(&hole[hole_index..]);
+    bridged.extend_from_slice(&hole[..=hole_index]);
+    bridged.push(polygon[p_index]);
+    bridged.extend_from_slice(&polygon[(p_index + 1)..]);
+    Some(bridged)
+}
+
+/// Clips the ears of the counter clockwise (weakly) simple polygon.
+fn clip_ears(polygon: &[Point]) -> Vec<[Point; 3]> {
+    let mut remaining = (0..polygon.len()).collect::<Vec<usize>>();
+    let mut triangles = Vec::with_capacity(polygon.len().saturating_sub(2));
+
+    while remaining.len() > 3 {
+        let len = remaining.len();
+        let corner = |index: usize| {
+            (
+                polygon[remaining[(index + len - 1) % len]],
+                polygon[remaining[index]],
+                polygon[remaining[(index + 1) % len]],
+            )
+        };
+
+        let ear = (0..len).find(|index| {
+            let (prev, current, next) = corner(*index);
+            let area = cross(prev, current, next);
+            if area.abs() <= EPSILON {
+                // Collinear corners (e.g. the tips of hole bridges) are removed without triangle.
+                return true;
+            }
+            area > 0f64
+                && remaining.iter().all(|other| {
+                    let point = polygon[*other];
+                    point == prev
+                        || point == current
+                        || point == next
+                        || !in_triangle(prev, current, next, point)
+                })
+        });
+        // Rounding errors may leave no proper ear, then any convex corner is clipped.
+        let ear = ear.or_else(|| {
+            (0..len).find(|index| {
+                let (prev, current, next) = corner(*index);
+                cross(prev, current, next) > 0f64
+            })
+        });
+
+        match ear {
+            Some(index) => {
+                let (prev, current, next) = corner(index);
+                if cross(prev, current, next).abs() > EPSILON {
+                    triangles.push([prev, current, next]);
+                }
+                let _ = remaining.remove(index);
+            }
+            None => break,
+        }
+    }
+    if remaining.len() == 3 {
+        let (a, b, c) = (
+            polygon[remaining[0]],
+            polygon[remaining[1]],
+            polygon[remaining[2]],
+        );
+        if cross(a, b, c).abs() > EPSILON {
+            triangles.push([a, b, c]);
+        }
+    }
+    triangles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn area(triangles: &[[Point; 3]]) -> f64 {
+        triangles
+            .iter()
+            .map(|[a, b, c]| cross(*a, *b, *c).abs() / 2f64)
+            .sum()
+    }
+
+    fn centroid([a, b, c]: &[Point; 3]) -> Point {
+        [(a[0] + b[0] + c[0]) / 3f64, (a[1] + b[1] + c[1]) / 3f64]
+    }
+
+    fn square(center: Point, edge_length: f64) -> Vec<Point> {
+        let half = edge_length / 2f64;
+        vec![
+            [center[0] - half, center[1] - half],
+            [center[0] + half, center[1] - half],
+            [center[0] + half, center[1] + half],
+            [center[0] - half, center[1] + half],
+        ]
+    }
+
+    fn positions(points: &[Point]) -> Vec<Position2D> {
+        points
+            .iter()
+            .map(|point| Position2D::with(point[0], point[1]))
+            .collect()
+    }
+
+    #[test]
+    fn triangulates_convex_polygon() {
+        let triangles = triangulate(&square([0f64, 0f64], 2f64), &[]).unwrap();
+        assert_eq!(triangles.len(), 2);
+        assert!((area(&triangles) - 4f64).abs() < 1e-9);
+    }
+
+    #[test]
+    fn triangulates_concave_polygon_in_both_orientations() {
+        // An arrow pointing right, whose notch at (1, 0) is a reflex corner.
+        let mut arrow = vec![[0f64, -1f64], [3f64, 0f64], [0f64, 1f64], [1f64, 0f64]];
+        for _ in 0..2 {
+            let triangles = triangulate(&arrow, &[]).unwrap();
+            assert_eq!(triangles.len(), 2);
+            assert!((area(&triangles) - 2f64).abs() < 1e-9);
+            assert!(triangles
+                .iter()
+                .all(|triangle| contains(&arrow, centroid(triangle))));
+            arrow.reverse();
+        }
+    }
+
+    #[test]
+    fn triangulates_polygon_with_holes() {
+        let outline = square([0f64, 0f64], 8f64);
+        let holes = vec![square([-2f64, 0f64], 2f64), square([2f64, 1f64], 2f64)];
+        let triangles = triangulate(&outline, &holes).unwrap();
+        assert!((area(&triangles) - (64f64 - 4f64 - 4f64)).abs() < 1e-9);
+        assert!(triangles.iter().all(|triangle| {
+            let centroid = centroid(triangle);
+            contains(&outline, centroid) && holes.iter().all(|hole| !contains(hole, centroid))
+        }));
+    }
+
+    #[test]
+    fn ignores_repeated_closing_point() {
+        let mut outline = square([0f64, 0f64], 2f64);
+        outline.push(outline[0]);
+        assert_eq!(triangulate(&outline, &[]).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn rejects_too_few_points() {
+        assert_eq!(
+            triangulate(&[[0f64, 0f64], [1f64, 0f64], [1f64, 0f64]], &[]),
+            Err(TriangulationError::TooFewPoints { ring: 0, points: 2 })
+        );
+    }
+
+    #[test]
+    fn rejects_self_intersecting_polygon() {
+        let bow_tie = [[0f64, 0f64], [2f64, 2f64], [2f64, 0f64], [0f64, 2f64]];
+        assert!(matches!(
+            triangulate(&bow_tie, &[]),
+            Err(TriangulationError::SelfIntersecting { ring: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn rejects_hole_crossing_outline() {
+        let holes = vec![square([1f64, 0f64], 2f64)];
+        assert!(matches!(
+            triangulate(&square([0f64, 0f64], 2f64), &holes),
+            Err(TriangulationError::SelfIntersecting {
+                ring: 0,
+                other_ring: 1,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn rejects_holes_outside_outline() {
+        let outline = square([0f64, 0f64], 2f64);
+        for center in [[-4f64, 0f64], [4f64, 0f64], [0f64, 4f64]].iter() {
+            assert_eq!(
+                triangulate(&outline, &[square(*center, 1f64)]),
+                Err(TriangulationError::HoleOutsideOutline { ring: 1 })
+            );
+        }
+    }
+
+    #[test]
+    fn polygon_with_holes_fills_and_outlines_every_ring() {
+        let outline = positions(&square([0f64, 0f64], 4f64));
+        let hole = positions(&square([0f64, 0f64], 2f64));
+        let geometry = polygon_with_holes(
+            &outline,
+            std::slice::from_ref(&hole),
+            Color::black(),
+            Color::black(),
+            1f64,
+            Transformation2D::identity(),
+        )
+        .unwrap();
+        let geometries = match geometry {
+            Geometry2D::Group(geometries) => geometries,
+            other => panic!("expected a group, got {:?}", other),
+        };
+        let (triangles, outlines): (Vec<Geometry2D>, Vec<Geometry2D>) = geometries
+            .into_iter()
+            .partition(|geometry| matches!(geometry, Geometry2D::Triangle { .. }));
+        let filled_area = triangles
+            .iter()
+            .map(|triangle| match triangle {
+                Geometry2D::Triangle { points, .. } => {
+                    cross(
+                        [points[0].x, points[0].y],
+                        [points[1].x, points[1].y],
+                        [points[2].x, points[2].y],
+                    )
+                    .abs()
+                        / 2f64
+                }
+                _ => unreachable!(),
+            })
+            .sum::<f64>();
+        assert!((filled_area - 12f64).abs() < 1e-9);
+        assert_eq!(outlines.len(), 2);
+        for (outline, ring) in outlines.iter().zip([&outline, &hole].iter()) {
+            match outline {
+                Geometry2D::Polyline { points, .. } => {
+                    assert_eq!(points.len(), ring.len() + 1);
+                    assert_eq!(points.first(), points.last());
+                }
+                other => panic!("expected a polyline, got {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn polygon_with_holes_reports_hole_outside_outline() {
+        let result = polygon_with_holes(
+            &positions(&square([0f64, 0f64], 2f64)),
+            &[positions(&square([-4f64, 0f64], 1f64))],
+            Color::black(),
+            Color::black(),
+            1f64,
+            Transformation2D::identity(),
+        );
+        assert_eq!(
+            result.err(),
+            Some(TriangulationError::HoleOutsideOutline { ring: 1 })
+        );
+    }
+}
@@ -0,0 +1,157 @@
+//! Lock-free triple buffer, which hands the frames from `render_two_dimensional` over to the
+//! render thread without either side ever waiting for the other.
+//!
+//! Each of the three slots is owned by exactly one of the input, the output or the buffer
+//! itself ("middle") at any time. Publishing swaps the slot of the input with the middle one
+//! and taking swaps the slot of the output with the middle one, both with a single atomic
+//! operation, so only the owner of a slot ever accesses it.
+
+use std::cell::UnsafeCell;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Arc;
+
+/// Bit of `middle` which marks that the middle slot holds a value the output hasn't taken yet.
+const FRESH: u8 = 0b100;
+const INDEX: u8 = 0b011;
+
+struct Shared<T> {
+    slots: [UnsafeCell<Option<T>>; 3],
+    middle: AtomicU8,
+}
+
+// The slots are only accessed by their current owner, see the module documentation.
+unsafe impl<T: Send> Sync for Shared<T> {}
+
+/// Creates a connected input and output.
+pub(crate) fn triple_buffer<T: Send>() -> (TripleBufferInput<T>, TripleBufferOutput<T>) {
+    let shared = Arc::new(Shared {
+        slots: [
+            UnsafeCell::new(None),
+            UnsafeCell::new(None),
+            UnsafeCell::new(None),
+        ],
+        middle: AtomicU8::new(1),
+    });
+    (
+        TripleBufferInput {
+            shared: Arc::clone(&shared),
+            index: 0,
+        },
+        TripleBufferOutput { shared, index: 2 },
+    )
+}
+
+/* --- --- --- TripleBufferInput --- --- --- */
+
+pub(crate) struct TripleBufferInput<T> {
+    shared: Arc<Shared<T>>,
+    index: u8,
+}
+
+impl<T: Send> TripleBufferInput<T> {
+    /// Makes `value` the latest value and returns the previous one, if the output didn't take
+    /// it in the meantime.
+    pub fn publish(&mut self, value: T) -> Option<T> {
+        // SAFETY: The slot at `index` is owned by the input.
+        unsafe {
+            *self.shared.slots[self.index as usize].get() = Some(value);
+        }
+        let previous = self
+            .shared
+            .middle
+            .swap(self.index | FRESH, Ordering::AcqRel);
+        self.index = previous & INDEX;
+        if previous & FRESH != 0 {
+            // SAFETY: The slot at `index` was just handed over to the input by the swap.
+            unsafe { (*self.shared.slots[self.index as usize].get()).take() }
+        } else {
+            None
+        }
+    }
+}
+
+/* --- --- --- TripleBufferOutput --- --- --- */
+
+pub(crate) struct TripleBufferOutput<T> {
+    shared: Arc<Shared<T>>,
+    index: u8,
+}
+
+impl<T: Send> TripleBufferOutput<T> {
+    /// Takes the latest value, if one was published since the last call.
+    pub fn take(&mut self) -> Option<T> {
+        if self.shared.middle.load(Ordering::Relaxed) & FRESH == 0 {
+            return None;
+        }
+        let previous = self.shared.middle.swap(self.index, Ordering::AcqRel);
+        self.index = previous & INDEX;
+        // SAFETY: The slot at `index` was just handed over to the output by the swap.
+        unsafe { (*self.shared.slots[self.index as usize].get()).take() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::thread;
+
+    #[test]
+    fn take_returns_nothing_before_publishing() {
+        let (_input, mut output) = triple_buffer::<u32>();
+        assert_eq!(output.take(), None);
+    }
+
+    #[test]
+    fn take_returns_latest_value_once() {
+        let (mut input, mut output) = triple_buffer();
+        assert_eq!(input.publish(1), None);
+        assert_eq!(output.take(), Some(1));
+        assert_eq!(output.take(), None);
+        assert_eq!(input.publish(2), None);
+        assert_eq!(input.publish(3), Some(2));
+        assert_eq!(output.take(), Some(3));
+        assert_eq!(output.take(), None);
+    }
+
+    #[test]
+    fn publish_returns_replaced_value_only_if_not_taken() {
+        let (mut input, mut output) = triple_buffer();
+        assert_eq!(input.publish(1), None);
+        assert_eq!(input.publish(2), Some(1));
+        assert_eq!(input.publish(3), Some(2));
+        assert_eq!(output.take(), Some(3));
+        assert_eq!(input.publish(4), None);
+        assert_eq!(input.publish(5), Some(4));
+        assert_eq!(output.take(), Some(5));
+        assert_eq!(input.publish(6), None);
+    }
+
+    #[test]
+    fn every_value_is_either_taken_in_order_or_replaced() {
+        const VALUES: u64 = 200_000;
+        let (mut input, mut output) = triple_buffer();
+        let producer = thread::spawn(move || {
+            let mut replaced = 0u64;
+            for value in 0..VALUES {
+                if input.publish(value).is_some() {
+                    replaced += 1;
+                }
+            }
+            replaced
+        });
+        let mut taken = 0u64;
+        let mut last = None;
+        while last != Some(VALUES - 1) {
+            if let Some(value) = output.take() {
+                assert!(last.map_or(true, |last| value > last));
+                last = Some(value);
+                taken += 1;
+            } else {
+                thread::yield_now();
+            }
+        }
+        let replaced = producer.join().unwrap();
+        assert_eq!(taken + replaced, VALUES);
+    }
+}